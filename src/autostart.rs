@@ -0,0 +1,229 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+use crate::{log_info, log_warn, policy, settings::Settings};
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use anyhow::{Result, bail};
+    use std::path::Path;
+    use windows_sys::Win32::{
+        Foundation::{ERROR_FILE_NOT_FOUND, ERROR_SUCCESS},
+        System::Registry::{
+            HKEY_CURRENT_USER, REG_SZ, RRF_RT_REG_SZ, RegDeleteKeyValueW, RegGetValueW,
+            RegSetKeyValueW,
+        },
+    };
+
+    const RUN_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Run";
+    const VALUE_NAME: &str = "FigmaRichPresence";
+
+    fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain(Some(0)).collect()
+    }
+
+    pub fn is_enabled() -> bool {
+        let (key, name) = (wide(RUN_KEY), wide(VALUE_NAME));
+        let status = unsafe {
+            RegGetValueW(
+                HKEY_CURRENT_USER,
+                key.as_ptr(),
+                name.as_ptr(),
+                RRF_RT_REG_SZ,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            )
+        };
+        status == ERROR_SUCCESS
+    }
+
+    pub fn enable(exe: &Path) -> Result<()> {
+        let (key, name) = (wide(RUN_KEY), wide(VALUE_NAME));
+        let command = wide(&format!("\"{}\"", exe.display()));
+        let status = unsafe {
+            RegSetKeyValueW(
+                HKEY_CURRENT_USER,
+                key.as_ptr(),
+                name.as_ptr(),
+                REG_SZ,
+                command.as_ptr().cast(),
+                (command.len() * size_of::<u16>()) as u32,
+            )
+        };
+        if status != ERROR_SUCCESS {
+            bail!("failed to write the Run registry value (error {status})");
+        }
+        Ok(())
+    }
+
+    pub fn disable() -> Result<()> {
+        let (key, name) = (wide(RUN_KEY), wide(VALUE_NAME));
+        let status = unsafe { RegDeleteKeyValueW(HKEY_CURRENT_USER, key.as_ptr(), name.as_ptr()) };
+        if status != ERROR_SUCCESS && status != ERROR_FILE_NOT_FOUND {
+            bail!("failed to delete the Run registry value (error {status})");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod platform {
+    use anyhow::{Context, Result};
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    #[cfg(target_os = "macos")]
+    fn entry_path() -> Option<PathBuf> {
+        Some(
+            dirs::home_dir()?
+                .join("Library/LaunchAgents")
+                .join("com.figma-discord-rp.plist"),
+        )
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    fn entry_path() -> Option<PathBuf> {
+        Some(
+            dirs::config_dir()?
+                .join("autostart")
+                .join("figma-discord-rp.desktop"),
+        )
+    }
+
+    #[cfg(target_os = "macos")]
+    fn entry(exe: &Path) -> String {
+        let exe = exe
+            .display()
+            .to_string()
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>com.figma-discord-rp</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{exe}</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#
+        )
+    }
+
+    /// Quotes `exe` for the `Exec` key of a desktop entry, which has its
+    /// own escaping rules on top of the shell-like quoting.
+    #[cfg(not(target_os = "macos"))]
+    fn entry(exe: &Path) -> String {
+        let mut quoted = String::from("\"");
+        for c in exe.display().to_string().chars() {
+            match c {
+                '"' | '`' | '$' | '\\' => {
+                    quoted.push('\\');
+                    quoted.push(c);
+                }
+                '%' => quoted.push_str("%%"),
+                _ => quoted.push(c),
+            }
+        }
+        quoted.push('"');
+        format!(
+            "[Desktop Entry]\n\
+             Type=Application\n\
+             Name=Figma Rich Presence\n\
+             Exec={quoted}\n\
+             Terminal=false\n\
+             X-GNOME-Autostart-enabled=true\n"
+        )
+    }
+
+    pub fn is_enabled() -> bool {
+        entry_path().is_some_and(|path| path.exists())
+    }
+
+    pub fn enable(exe: &Path) -> Result<()> {
+        let path = entry_path().context("no home directory")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+        fs::write(&path, entry(exe)).with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    pub fn disable() -> Result<()> {
+        let Some(path) = entry_path() else {
+            return Ok(());
+        };
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("failed to remove {}", path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Whether the OS is set up to start the app at login.
+pub fn is_enabled() -> bool {
+    platform::is_enabled()
+}
+
+fn current_exe() -> Result<PathBuf> {
+    let exe = std::env::current_exe().context("failed to locate the executable")?;
+    Ok(exe.canonicalize().unwrap_or(exe))
+}
+
+/// Installs or removes the login entry (an autostart desktop file on Linux,
+/// a Run registry value on Windows, a LaunchAgent on macOS).
+fn set_enabled(enabled: bool) -> Result<()> {
+    if enabled {
+        platform::enable(&current_exe()?)
+    } else {
+        platform::disable()
+    }
+}
+
+/// Brings the login entry in line with `enabled`, logging failures since
+/// there's nobody to report them to.
+pub fn apply(enabled: bool) {
+    if is_enabled() == enabled {
+        return;
+    }
+    match set_enabled(enabled) {
+        Ok(()) if enabled => {
+            log_info!("autostart", "Enabled start on login");
+        }
+        Ok(()) => {
+            log_info!("autostart", "Disabled start on login");
+        }
+        Err(e) => {
+            log_warn!("autostart", "Failed to update start on login: {e:#}");
+        }
+    }
+}
+
+/// Picks up the login entry's actual state at startup, since it may have
+/// been added or removed outside the app. A managed value is enforced
+/// instead.
+pub fn detect(settings: &mut Settings) {
+    if policy::is_locked("launch_at_login") {
+        apply(settings.launch_at_login);
+        return;
+    }
+    let enabled = is_enabled();
+    if settings.launch_at_login != enabled {
+        settings.launch_at_login = enabled;
+        if let Err(e) = settings.save() {
+            log_warn!("autostart", "Failed to save detected state: {e:#}");
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use anyhow::{Context, Result, bail};
+use serde_json::Value;
+use std::{fs, path::Path};
+
+use crate::{
+    policy,
+    settings::{Settings, write_atomic},
+};
+
+/// Fields that describe one person's setup rather than the team's look, and
+/// are left out of exports unless asked for.
+pub const PERSONAL_KEYS: &[&str] = &[
+    "hide_filename",
+    "disable_idle",
+    "launch_at_login",
+    "privacy",
+    "schedule",
+    "advanced",
+    "appearance",
+    "notifications",
+    "language",
+    "active_profile",
+    "profiles",
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Change {
+    pub key: String,
+    pub old: String,
+    pub new: String,
+}
+
+pub fn format_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Flattens nested objects (and arrays of objects) into dotted keys.
+pub fn flatten(prefix: &str, value: &Value, out: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                let key = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten(&key, value, out);
+            }
+        }
+        Value::Array(items) if items.iter().any(Value::is_object) => {
+            for (i, value) in items.iter().enumerate() {
+                flatten(&format!("{prefix}.{i}"), value, out);
+            }
+        }
+        other => out.push((prefix.to_string(), format_value(other))),
+    }
+}
+
+pub fn export(settings: &Settings, include_personal: bool) -> Result<Value> {
+    let mut doc = settings.to_value()?;
+    if let Value::Object(map) = &mut doc {
+        map.remove("version");
+        if !include_personal {
+            map.retain(|key, _| !PERSONAL_KEYS.contains(&key.as_str()));
+        }
+    }
+    Ok(doc)
+}
+
+pub fn export_to_file(settings: &Settings, path: &Path, include_personal: bool) -> Result<()> {
+    let data = serde_json::to_string_pretty(&export(settings, include_personal)?)?;
+    write_atomic(path, data.as_bytes())
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+pub fn read(path: &Path) -> Result<Value> {
+    let data =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let bundle: Value = serde_json::from_str(&data)
+        .with_context(|| format!("{} is not valid JSON", path.display()))?;
+    if !bundle.is_object() {
+        bail!("{} is not a settings bundle", path.display());
+    }
+    Ok(bundle)
+}
+
+/// Merges `bundle` over `settings`. Fields locked by the policy keep their
+/// managed values.
+pub fn import(settings: &Settings, bundle: &Value) -> Result<Settings> {
+    let mut doc = settings.to_value()?;
+    let mut bundle = bundle.clone();
+    if let Value::Object(map) = &mut bundle {
+        map.remove("version");
+    }
+    policy::merge(&mut doc, &bundle);
+    let mut imported: Settings =
+        serde_json::from_value(doc).context("bundle contains invalid values")?;
+    imported.normalize();
+    imported.validate()?;
+    Ok(imported)
+}
+
+pub fn diff(old: &Settings, new: &Settings) -> Result<Vec<Change>> {
+    let (mut before, mut after) = (Vec::new(), Vec::new());
+    flatten("", &old.to_value()?, &mut before);
+    flatten("", &new.to_value()?, &mut after);
+
+    let mut changes: Vec<Change> = after
+        .iter()
+        .filter(|(key, value)| before.iter().all(|(k, v)| k != key || v != value))
+        .map(|(key, value)| Change {
+            key: key.clone(),
+            old: before
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.clone())
+                .unwrap_or_default(),
+            new: value.clone(),
+        })
+        .collect();
+    changes.extend(
+        before
+            .iter()
+            .filter(|(key, _)| after.iter().all(|(k, _)| k != key))
+            .map(|(key, value)| Change {
+                key: key.clone(),
+                old: value.clone(),
+                new: String::new(),
+            }),
+    );
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::{AppName, test_env::TestEnv};
+    use serde_json::json;
+
+    fn shared() -> Settings {
+        Settings {
+            default_image: "team_logo".to_string(),
+            app_name: AppName::Custom("Design".to_string()),
+            hide_filename: true,
+            ..Settings::default()
+        }
+    }
+
+    #[test]
+    fn export_leaves_out_personal_keys() {
+        let _env = TestEnv::new();
+        let doc = export(&shared(), false).unwrap();
+        assert_eq!(doc["default_image"], json!("team_logo"));
+        assert_eq!(doc.get("version"), None);
+        for key in PERSONAL_KEYS {
+            assert_eq!(doc.get(key), None, "{key}");
+        }
+        let doc = export(&shared(), true).unwrap();
+        assert_eq!(doc["hide_filename"], json!(true));
+    }
+
+    #[test]
+    fn import_applies_an_export() {
+        let _env = TestEnv::new();
+        let bundle = export(&shared(), false).unwrap();
+        let imported = import(&Settings::default(), &bundle).unwrap();
+        assert_eq!(imported.default_image, "team_logo");
+        assert_eq!(imported.app_name, AppName::Custom("Design".to_string()));
+        assert!(!imported.hide_filename);
+    }
+
+    #[test]
+    fn import_rejects_invalid_values() {
+        let _env = TestEnv::new();
+        let settings = Settings::default();
+        for bundle in [
+            json!({"hide_filename": "yes"}),
+            json!({"default_image": "file:///icon.png"}),
+            json!({"advanced": {"idle_threshold_secs": 0}}),
+            json!({"image_overrides": {"unknown_state": {"enabled": true}}}),
+        ] {
+            assert!(import(&settings, &bundle).is_err(), "{bundle}");
+        }
+    }
+
+    #[test]
+    fn import_keeps_managed_values() {
+        let _env = TestEnv::with_policy(json!({"default_image": "managed"}));
+        let imported = import(&Settings::default(), &json!({"default_image": "other"})).unwrap();
+        assert_eq!(imported.default_image, "managed");
+    }
+
+    #[test]
+    fn read_rejects_files_that_arent_bundles() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bundle.json");
+        assert!(read(&path).is_err());
+        fs::write(&path, "{ not json").unwrap();
+        assert!(read(&path).is_err());
+        fs::write(&path, "[1, 2]").unwrap();
+        assert!(read(&path).is_err());
+        fs::write(&path, r#"{"default_image": "team_logo"}"#).unwrap();
+        assert_eq!(read(&path).unwrap(), json!({"default_image": "team_logo"}));
+    }
+}
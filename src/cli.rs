@@ -0,0 +1,380 @@
+use anyhow::{Context, Result, anyhow, bail};
+use serde_json::{Map, Value};
+use std::path::Path;
+
+use crate::{
+    bundle::{self, flatten, format_value},
+    instance::{self, Command, Reply, Status},
+    policy,
+    settings::{self, Settings},
+};
+
+const CONFIG_USAGE: &str = "Usage: figma-discord-rp config <command> [--json]
+
+Commands:
+  list                 Show all settings
+  get <key>            Show one setting, e.g. image_overrides.design.enabled
+  set <key> <value>    Change a setting
+  reset [key]          Restore the default for one setting, or all of them
+  export <file> [--all]
+                       Write shareable settings, --all includes personal ones
+  import <file>        Apply settings from an exported file";
+
+/// Subcommands that talk to the running instance, see [`run_control`].
+pub const CONTROL_COMMANDS: &[&str] = &["status", "pause", "resume", "reload", "quit"];
+
+const CONTROL_USAGE: &str = "Usage: figma-discord-rp <command> [--json]
+
+Commands:
+  status               Show what the running instance is doing
+  pause [minutes]      Pause presence, until resumed unless minutes are given
+  resume               Resume presence
+  reload               Re-read the settings file
+  quit                 Stop the running instance";
+
+pub fn run_config(args: &[String]) -> i32 {
+    crate::logging::log_to_stderr();
+    let json = args.iter().any(|a| a == "--json");
+    let all = args.iter().any(|a| a == "--all");
+    let args: Vec<&str> = args
+        .iter()
+        .map(String::as_str)
+        .filter(|a| *a != "--json" && *a != "--all")
+        .collect();
+
+    let result = match args.as_slice() {
+        ["list"] => list(json),
+        ["get", key] => get(key, json),
+        ["set", key, value] => set(key, value, json),
+        ["reset"] => reset(None),
+        ["reset", key] => reset(Some(key)),
+        ["export", file] => export(Path::new(file), all),
+        ["import", file] => import(Path::new(file), json),
+        _ => {
+            eprintln!("{CONFIG_USAGE}");
+            return 2;
+        }
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("error: {e:#}");
+            1
+        }
+    }
+}
+
+fn lookup<'a>(doc: &'a Value, key: &str) -> Option<&'a Value> {
+    key.split('.').try_fold(doc, |value, part| match value {
+        Value::Object(map) => map.get(part),
+        Value::Array(items) => items.get(part.parse::<usize>().ok()?),
+        _ => None,
+    })
+}
+
+/// Like [`lookup`], but creates the final segment when its parent is a map,
+/// so keys such as `image_overrides.<state>` can be added.
+fn lookup_mut<'a>(doc: &'a mut Value, key: &str) -> Option<&'a mut Value> {
+    let (parent, last) = match key.rsplit_once('.') {
+        Some((parent, last)) => (lookup_parent_mut(doc, parent)?, last),
+        None => (doc, key),
+    };
+    match parent {
+        Value::Object(map) => Some(map.entry(last).or_insert(Value::Null)),
+        Value::Array(items) => items.get_mut(last.parse::<usize>().ok()?),
+        _ => None,
+    }
+}
+
+fn lookup_parent_mut<'a>(doc: &'a mut Value, key: &str) -> Option<&'a mut Value> {
+    key.split('.').try_fold(doc, |value, part| match value {
+        Value::Object(map) => map.get_mut(part),
+        Value::Array(items) => items.get_mut(part.parse::<usize>().ok()?),
+        _ => None,
+    })
+}
+
+fn list(json: bool) -> Result<()> {
+    let doc = Settings::load().to_value()?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&doc)?);
+        return Ok(());
+    }
+    let mut entries = Vec::new();
+    flatten("", &doc, &mut entries);
+    for (key, value) in entries {
+        println!("{key} = {value}");
+    }
+    Ok(())
+}
+
+fn get(key: &str, json: bool) -> Result<()> {
+    let doc = Settings::load().to_value()?;
+    let value = lookup(&doc, key).ok_or_else(|| anyhow!("unknown key \"{key}\""))?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(value)?);
+    } else {
+        println!("{}", format_value(value));
+    }
+    Ok(())
+}
+
+/// Values are parsed as JSON (`true`, `5`, `["zoom"]`, `{"Custom": "Name"}`),
+/// except that scalars are taken verbatim where the current value is a string.
+fn parse_value(current: &Value, raw: &str) -> Value {
+    match serde_json::from_str(raw) {
+        Ok(value @ (Value::Object(_) | Value::Array(_))) => value,
+        Ok(_) if current.is_string() => Value::String(raw.to_string()),
+        Ok(value) => value,
+        Err(_) => Value::String(raw.to_string()),
+    }
+}
+
+fn apply(settings: &Settings, key: &str, value: Value) -> Result<Settings> {
+    if policy::is_locked(key) {
+        bail!("\"{key}\" is managed by {}", policy::path().display());
+    }
+    let mut doc = settings.to_value()?;
+    let slot = lookup_mut(&mut doc, key).ok_or_else(|| anyhow!("unknown key \"{key}\""))?;
+    *slot = value.clone();
+
+    let updated: Settings =
+        serde_json::from_value(doc).with_context(|| format!("invalid value for \"{key}\""))?;
+    if lookup(&updated.to_value()?, key) != Some(&value) {
+        bail!("unknown key \"{key}\"");
+    }
+    updated.validate()?;
+    Ok(updated)
+}
+
+fn set(key: &str, raw: &str, json: bool) -> Result<()> {
+    let mut value = Value::Null;
+    settings::update(|settings| {
+        let current = lookup(&settings.to_value()?, key)
+            .cloned()
+            .unwrap_or(Value::Null);
+        value = parse_value(&current, raw);
+        *settings = apply(settings, key, value.clone())?;
+        Ok(())
+    })?;
+
+    if json {
+        let mut out = Map::new();
+        out.insert(key.to_string(), value);
+        println!("{}", serde_json::to_string_pretty(&out)?);
+    } else {
+        println!("{key} = {}", format_value(&value));
+    }
+    Ok(())
+}
+
+fn reset(key: Option<&str>) -> Result<()> {
+    let Some(key) = key else {
+        Settings::default().save()?;
+        println!("All settings reset to defaults");
+        return Ok(());
+    };
+    let defaults = Settings::default().to_value()?;
+    let value = lookup(&defaults, key)
+        .cloned()
+        .ok_or_else(|| anyhow!("\"{key}\" has no default"))?;
+    settings::update(|settings| {
+        *settings = apply(settings, key, value.clone())?;
+        Ok(())
+    })?;
+    println!("{key} = {}", format_value(&value));
+    Ok(())
+}
+
+fn export(path: &Path, include_personal: bool) -> Result<()> {
+    bundle::export_to_file(&Settings::load(), path, include_personal)?;
+    println!("Exported settings to {}", path.display());
+    Ok(())
+}
+
+fn import(path: &Path, json: bool) -> Result<()> {
+    let bundle = bundle::read(path)?;
+    let mut changes = Vec::new();
+    settings::update(|settings| {
+        let imported = bundle::import(settings, &bundle)?;
+        changes = bundle::diff(settings, &imported)?;
+        *settings = imported;
+        Ok(())
+    })?;
+
+    if json {
+        let out: Map<String, Value> = changes
+            .into_iter()
+            .map(|c| (c.key, Value::String(c.new)))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&out)?);
+    } else if changes.is_empty() {
+        println!("No changes");
+    } else {
+        for change in changes {
+            println!("{}: {} -> {}", change.key, change.old, change.new);
+        }
+    }
+    Ok(())
+}
+
+pub fn run_control(args: &[String]) -> i32 {
+    crate::logging::log_to_stderr();
+    let json = args.iter().any(|a| a == "--json");
+    let args: Vec<&str> = args
+        .iter()
+        .map(String::as_str)
+        .filter(|a| *a != "--json")
+        .collect();
+
+    let command = match args.as_slice() {
+        ["status"] => Command::Status,
+        ["pause"] => Command::Pause { minutes: None },
+        ["pause", minutes] => match minutes.parse::<u32>() {
+            Ok(minutes) if minutes > 0 => Command::Pause {
+                minutes: Some(minutes),
+            },
+            _ => {
+                eprintln!("{CONTROL_USAGE}");
+                return 2;
+            }
+        },
+        ["resume"] => Command::Resume,
+        ["reload"] => Command::Reload,
+        ["quit"] => Command::Quit,
+        _ => {
+            eprintln!("{CONTROL_USAGE}");
+            return 2;
+        }
+    };
+
+    match control(command, json) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("error: {e:#}");
+            1
+        }
+    }
+}
+
+fn control(command: Command, json: bool) -> Result<()> {
+    let reply = instance::request(command)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&reply)?);
+    }
+    match reply {
+        Reply::Error(message) => Err(anyhow!(message)),
+        Reply::Status(status) if !json => {
+            print_status(&status);
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn print_status(status: &Status) {
+    let connection = |connected| {
+        if connected {
+            "connected"
+        } else {
+            "disconnected"
+        }
+    };
+    println!("Version: {}", status.version);
+    println!("Profile: {}", status.profile);
+    println!("Discord: {}", connection(status.discord_connected));
+    println!("Figma: {}", connection(status.figma_connected));
+    match (&status.file, &status.activity) {
+        (Some(file), Some(activity)) => println!("File: {file} ({activity})"),
+        (Some(file), None) => println!("File: {file}"),
+        (None, _) => println!("File: none"),
+    }
+    match (status.paused, status.paused_until) {
+        (false, _) => println!("Paused: no"),
+        (true, Some(until)) => println!("Paused: until {}", until.format("%Y-%m-%d %H:%M")),
+        (true, None) => println!("Paused: until resumed"),
+    }
+    match &status.privacy {
+        Some(process) => println!("Privacy mode: on ({process})"),
+        None => println!("Privacy mode: off"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::test_env::TestEnv;
+    use serde_json::json;
+
+    #[test]
+    fn apply_sets_nested_keys() {
+        let _env = TestEnv::new();
+        let mut settings = Settings::default();
+        let updated = apply(&settings, "hide_filename", json!(true)).unwrap();
+        assert!(updated.hide_filename);
+        let updated = apply(&settings, "advanced.idle_threshold_secs", json!(600)).unwrap();
+        assert_eq!(updated.advanced.idle_threshold_secs, 600);
+        let updated = apply(
+            &settings,
+            "image_overrides.idle",
+            json!({"enabled": true, "image_url": "figma_idle"}),
+        )
+        .unwrap();
+        assert_eq!(updated.image_overrides["idle"].image_url, "figma_idle");
+
+        // The active profile mirrors the top-level fields, so edit another.
+        settings.profiles.push(settings::Profile {
+            name: "Work".to_string(),
+            ..settings.profiles[0].clone()
+        });
+        let updated = apply(&settings, "profiles.1.hide_filename", json!(true)).unwrap();
+        assert!(updated.profiles[1].hide_filename);
+    }
+
+    #[test]
+    fn apply_rejects_unknown_keys() {
+        let _env = TestEnv::new();
+        let settings = Settings::default();
+        for key in [
+            "no_such_key",
+            "advanced.no_such_key",
+            "hide_filename.inner",
+            "profiles.9.hide_filename",
+        ] {
+            let err = apply(&settings, key, json!(true)).unwrap_err();
+            assert!(err.to_string().contains("unknown key"), "{key}: {err:#}");
+        }
+    }
+
+    #[test]
+    fn apply_rejects_type_mismatches_and_invalid_values() {
+        let _env = TestEnv::new();
+        let settings = Settings::default();
+        let err = apply(&settings, "hide_filename", json!("yes")).unwrap_err();
+        assert!(err.to_string().contains("invalid value"), "{err:#}");
+        assert!(apply(&settings, "advanced.idle_threshold_secs", json!(-1)).is_err());
+        assert!(apply(&settings, "advanced.idle_threshold_secs", json!(1_000_000)).is_err());
+        assert!(apply(&settings, "default_image", json!("file:///icon.png")).is_err());
+    }
+
+    #[test]
+    fn apply_rejects_policy_locked_keys() {
+        let _env = TestEnv::with_policy(json!({"advanced": {"battery_saver": false}}));
+        let settings = Settings::default();
+        for key in ["advanced", "advanced.battery_saver"] {
+            let err = apply(&settings, key, json!(true)).unwrap_err();
+            assert!(err.to_string().contains("is managed"), "{key}: {err:#}");
+        }
+        assert!(apply(&settings, "advanced.idle_threshold_secs", json!(600)).is_ok());
+    }
+
+    #[test]
+    fn parse_value_keeps_strings_verbatim() {
+        assert_eq!(parse_value(&json!("name"), "true"), json!("true"));
+        assert_eq!(parse_value(&json!(false), "true"), json!(true));
+        assert_eq!(parse_value(&json!(1), "5"), json!(5));
+        assert_eq!(parse_value(&json!([]), r#"["zoom"]"#), json!(["zoom"]));
+        assert_eq!(parse_value(&json!(null), "figma"), json!("figma"));
+    }
+}
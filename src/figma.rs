@@ -1,10 +1,12 @@
-use anyhow::{Result, anyhow};
+use anyhow::{Context, Result, anyhow};
 use serde::Deserialize;
-use std::{fmt, fs, path::PathBuf, time::Instant};
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System};
 
-pub const IDLE_THRESHOLD_SECONDS: u64 = 300;
-
 #[derive(Deserialize, Debug, Default)]
 struct FigmaSettings {
     #[serde(rename = "zoomStop")]
@@ -50,21 +52,19 @@ impl EditorType {
             Self::Other => "other",
         }
     }
-}
 
-impl fmt::Display for EditorType {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let s = match self {
-            EditorType::Design => "Designing",
-            EditorType::Whiteboard => "Whiteboarding",
-            EditorType::Slides => "Presenting",
-            EditorType::Sites => "Building a site",
-            EditorType::Buzz => "Buzzing",
-            EditorType::Make => "Making",
-            EditorType::DevMode => "Dev Mode",
-            EditorType::Other => "Working",
-        };
-        write!(f, "{s}")
+    /// The Figma product the editor belongs to, which isn't translated.
+    pub fn product_name(&self) -> &'static str {
+        match self {
+            Self::Design => "Figma Design",
+            Self::Whiteboard => "FigJam",
+            Self::Slides => "Figma Slides",
+            Self::Sites => "Figma Sites",
+            Self::Buzz => "Figma Buzz",
+            Self::Make => "Figma Make",
+            Self::DevMode => "Dev Mode",
+            Self::Other => "Figma",
+        }
     }
 }
 
@@ -77,18 +77,42 @@ pub struct FigmaTab {
     pub is_library: Option<bool>,
     #[serde(rename = "lastViewedAt")]
     pub last_viewed_at: Option<i64>,
+    /// The file's path on figma.com, e.g. `/design/<key>/<name>`.
+    pub path: Option<String>,
+}
+
+impl FigmaTab {
+    /// Identifies the file for [`FileRule`](crate::settings::FileRule)s: the
+    /// key from its path, or the title for tabs without one.
+    pub fn file_id(&self) -> Option<String> {
+        let key = self
+            .path
+            .as_deref()
+            .and_then(|path| path.trim_start_matches('/').split('/').nth(1))
+            .filter(|key| !key.is_empty());
+        key.or(self.title.as_deref()).map(str::to_string)
+    }
+
+    pub fn link(&self) -> Option<String> {
+        self.path
+            .as_deref()
+            .filter(|path| path.starts_with('/'))
+            .map(|path| format!("https://www.figma.com{path}"))
+    }
 }
 
 #[derive(Clone, Debug, Default)]
 pub struct FigmaState {
     pub active_tab: Option<FigmaTab>,
+    /// Tabs open across all windows, most recently viewed first.
+    pub open_tabs: Vec<FigmaTab>,
     pub last_focused_at: Option<Instant>,
 }
 
 impl FigmaState {
-    pub fn is_idle(&self) -> bool {
+    pub fn is_idle(&self, threshold: Duration) -> bool {
         match self.last_focused_at {
-            Some(ts) => ts.elapsed().as_secs() >= IDLE_THRESHOLD_SECONDS,
+            Some(ts) => ts.elapsed() >= threshold,
             None => true,
         }
     }
@@ -100,19 +124,15 @@ impl FigmaState {
             .unwrap_or(&EditorType::default())
             .key()
     }
-
-    pub fn status(&self) -> String {
-        self.active_tab
-            .as_ref()
-            .and_then(|t| t.editor_type.as_ref())
-            .unwrap_or(&EditorType::default())
-            .to_string()
-    }
 }
 
-pub fn find_figma_pids() -> Vec<u32> {
+pub fn scan_processes() -> System {
     let mut sys = System::new();
     sys.refresh_processes_specifics(ProcessesToUpdate::All, true, ProcessRefreshKind::nothing());
+    sys
+}
+
+pub fn find_figma_pids(sys: &System) -> Vec<u32> {
     sys.processes()
         .iter()
         .filter(|(_, p)| p.name().to_string_lossy().to_lowercase().contains("figma"))
@@ -151,22 +171,22 @@ fn get_figma_settings_path() -> Result<PathBuf> {
     }
 }
 
-pub fn scan_figma_active_tab() -> Result<Option<FigmaTab>> {
+pub fn scan_figma_tabs() -> Result<Vec<FigmaTab>> {
     let path = get_figma_settings_path()?;
 
     let raw =
         fs::read_to_string(&path).map_err(|e| anyhow!("failed to read Figma settings: {}", e))?;
 
     let settings: FigmaSettings =
-        serde_json::from_str(&raw).map_err(|e| anyhow!("failed to parse Figma settings: {}", e))?;
+        serde_json::from_str(&raw).context("failed to parse Figma settings")?;
 
-    let tab = settings
+    let mut tabs: Vec<FigmaTab> = settings
         .windows
         .unwrap_or_default()
-        .iter()
-        .flat_map(|w| w.tabs.iter().flatten())
-        .max_by_key(|t| t.last_viewed_at)
-        .cloned();
+        .into_iter()
+        .flat_map(|w| w.tabs.unwrap_or_default())
+        .collect();
+    tabs.sort_by_key(|t| std::cmp::Reverse(t.last_viewed_at));
 
-    Ok(tab)
+    Ok(tabs)
 }
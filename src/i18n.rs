@@ -0,0 +1,994 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, sync::OnceLock};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    English,
+    German,
+    French,
+    Spanish,
+}
+
+impl Language {
+    pub const ALL: [Language; 4] = [
+        Language::English,
+        Language::German,
+        Language::French,
+        Language::Spanish,
+    ];
+
+    /// The language's own name, as listed in the picker.
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+            Language::French => "Français",
+            Language::Spanish => "Español",
+        }
+    }
+
+    fn from_locale(tag: &str) -> Option<Self> {
+        let code = tag.split(['-', '_', '.']).next()?.to_ascii_lowercase();
+        match code.as_str() {
+            "en" => Some(Language::English),
+            "de" => Some(Language::German),
+            "fr" => Some(Language::French),
+            "es" => Some(Language::Spanish),
+            _ => None,
+        }
+    }
+
+    /// The first OS language there is a translation for, or English.
+    pub fn system() -> Self {
+        static SYSTEM: OnceLock<Language> = OnceLock::new();
+        *SYSTEM.get_or_init(|| {
+            sys_locale::get_locales()
+                .find_map(|tag| Self::from_locale(&tag))
+                .unwrap_or(Language::English)
+        })
+    }
+
+    pub fn strings(self) -> &'static Strings {
+        match self {
+            Language::English => &EN,
+            Language::German => &DE,
+            Language::French => &FR,
+            Language::Spanish => &ES,
+        }
+    }
+}
+
+/// Strings for the chosen language, or the OS language when none is set.
+pub fn strings(language: Option<Language>) -> &'static Strings {
+    language.unwrap_or_else(Language::system).strings()
+}
+
+/// Replaces the `{}` placeholders of a catalog entry with `args`, in order.
+pub fn fill(template: &str, args: &[&dyn fmt::Display]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut parts = template.split("{}");
+    if let Some(first) = parts.next() {
+        out.push_str(first);
+    }
+    for part in parts {
+        if let Some(arg) = args.next() {
+            out.push_str(&arg.to_string());
+        }
+        out.push_str(part);
+    }
+    out
+}
+
+/// Every user-facing string. Each language is a full instance of this
+/// struct, so a translation missing a key doesn't compile.
+#[cfg_attr(test, derive(Serialize))]
+pub struct Strings {
+    // Activity states, keyed like `settings::STATE_KEYS`.
+    pub state_design: &'static str,
+    pub state_whiteboard: &'static str,
+    pub state_slides: &'static str,
+    pub state_sites: &'static str,
+    pub state_buzz: &'static str,
+    pub state_make: &'static str,
+    pub state_dev_mode: &'static str,
+    pub state_other: &'static str,
+    pub state_idle: &'static str,
+
+    // Discord activity
+    pub file_details: &'static str,
+    pub unknown_file: &'static str,
+    pub untitled: &'static str,
+
+    // Tray menu and tooltip
+    pub figma_connecting: &'static str,
+    pub figma_connected: &'static str,
+    pub figma_disconnected: &'static str,
+    pub discord_connecting: &'static str,
+    pub discord_connected: &'static str,
+    pub discord_disconnected: &'static str,
+    pub privacy_on: &'static str,
+    pub privacy_off: &'static str,
+    pub presence_paused: &'static str,
+    pub presence_schedule_off: &'static str,
+    pub presence_schedule_private: &'static str,
+    pub presence_active: &'static str,
+    pub menu_pause: &'static str,
+    pub pause_15_minutes: &'static str,
+    pub pause_1_hour: &'static str,
+    pub pause_until_tomorrow: &'static str,
+    pub pause_until_resumed: &'static str,
+    pub menu_resume: &'static str,
+    pub menu_profiles: &'static str,
+    pub menu_settings: &'static str,
+    pub menu_quit: &'static str,
+    pub menu_recent_files: &'static str,
+    pub file_hide_name: &'static str,
+    pub file_set_alias: &'static str,
+    pub file_exclude: &'static str,
+    pub file_copy_link: &'static str,
+    pub tooltip_privacy: &'static str,
+    pub tooltip_no_file: &'static str,
+    pub paused: &'static str,
+    pub paused_until: &'static str,
+    pub notify_discord_lost: &'static str,
+    pub notify_discord_lost_body: &'static str,
+    pub notify_discord_back: &'static str,
+    pub notify_discord_back_body: &'static str,
+    pub notify_figma_error: &'static str,
+    pub notify_settings_problem: &'static str,
+    pub notify_update: &'static str,
+    pub notify_update_body: &'static str,
+
+    // Schedule
+    pub schedule_enable: &'static str,
+    pub schedule_disable: &'static str,
+    pub schedule_privacy: &'static str,
+    /// Monday first.
+    pub weekdays: [&'static str; 7],
+
+    // Settings window
+    pub tab_general: &'static str,
+    pub tab_images: &'static str,
+    pub tab_privacy: &'static str,
+    pub tab_advanced: &'static str,
+    pub tab_about: &'static str,
+    pub preview_playing: &'static str,
+    pub preview_elapsed: &'static str,
+    pub preview_example: &'static str,
+    pub preview_privacy_cleared: &'static str,
+    pub preview_load_failed: &'static str,
+    pub profile: &'static str,
+    pub profile_new: &'static str,
+    pub profile_duplicate: &'static str,
+    pub profile_delete: &'static str,
+    pub profile_name: &'static str,
+    pub profile_rename: &'static str,
+    pub profile_copy: &'static str,
+    pub app_name: &'static str,
+    pub app_name_custom: &'static str,
+    pub app_name_hint: &'static str,
+    pub other_settings: &'static str,
+    pub hide_file_names: &'static str,
+    pub disable_idle: &'static str,
+    pub start_on_login: &'static str,
+    pub appearance: &'static str,
+    pub theme: &'static str,
+    pub theme_system: &'static str,
+    pub theme_light: &'static str,
+    pub theme_dark: &'static str,
+    pub interface_size: &'static str,
+    pub language: &'static str,
+    pub language_system: &'static str,
+    pub shortcuts_hint: &'static str,
+    pub activity_images: &'static str,
+    pub default_image: &'static str,
+    pub default_image_hint: &'static str,
+    pub image_hint: &'static str,
+    pub image_asset_key: &'static str,
+    pub image_url: &'static str,
+    pub image_checking: &'static str,
+    pub image_reachable: &'static str,
+    pub image_too_long: &'static str,
+    pub image_whitespace: &'static str,
+    pub image_scheme: &'static str,
+    pub image_no_host: &'static str,
+    pub image_invalid: &'static str,
+    pub check_images: &'static str,
+    pub check_images_hover: &'static str,
+    pub privacy_mode: &'static str,
+    pub privacy_enable: &'static str,
+    pub privacy_clear: &'static str,
+    pub privacy_processes: &'static str,
+    pub files: &'static str,
+    pub files_hint: &'static str,
+    pub file_alias: &'static str,
+    pub schedule: &'static str,
+    pub schedule_rule_enabled: &'static str,
+    pub schedule_start: &'static str,
+    pub schedule_end: &'static str,
+    pub schedule_to: &'static str,
+    pub hour: &'static str,
+    pub minute: &'static str,
+    pub remove: &'static str,
+    pub add_rule: &'static str,
+    pub advanced: &'static str,
+    pub figma_polling: &'static str,
+    pub discord_updates: &'static str,
+    pub idle_after: &'static str,
+    pub battery_saver: &'static str,
+    pub notifications: &'static str,
+    pub notify_enabled: &'static str,
+    pub notify_discord: &'static str,
+    pub notify_figma: &'static str,
+    pub notify_settings: &'static str,
+    pub notify_updates: &'static str,
+    pub notify_interval: &'static str,
+    pub already_running: &'static str,
+    pub import_export: &'static str,
+    pub settings_file: &'static str,
+    pub export: &'static str,
+    pub exported_to: &'static str,
+    pub import: &'static str,
+    pub include_personal: &'static str,
+    pub import_no_changes: &'static str,
+    pub import_changes: &'static str,
+    pub apply: &'static str,
+    pub discard: &'static str,
+    pub imported: &'static str,
+    pub version: &'static str,
+    pub settings_folder: &'static str,
+    pub copy: &'static str,
+    pub managed_by: &'static str,
+    pub managed_notice: &'static str,
+    pub save: &'static str,
+    pub cancel: &'static str,
+    pub reset_defaults: &'static str,
+    pub unsaved_changes: &'static str,
+    pub unsaved_title: &'static str,
+    pub unsaved_prompt: &'static str,
+    pub keep_editing: &'static str,
+    pub save_failed: &'static str,
+    pub notice_reset: &'static str,
+    pub notice_unreadable: &'static str,
+    pub notice_newer_version: &'static str,
+    pub notice_invalid_edit: &'static str,
+}
+
+impl Strings {
+    /// Label of an activity state such as `design` or `idle`.
+    pub fn state(&self, key: &str) -> &'static str {
+        match key {
+            "design" => self.state_design,
+            "whiteboard" => self.state_whiteboard,
+            "slides" => self.state_slides,
+            "sites" => self.state_sites,
+            "buzz" => self.state_buzz,
+            "make" => self.state_make,
+            "dev_mode" => self.state_dev_mode,
+            "idle" => self.state_idle,
+            _ => self.state_other,
+        }
+    }
+
+    pub fn weekday(&self, day: chrono::Weekday) -> &'static str {
+        self.weekdays[day.num_days_from_monday() as usize]
+    }
+}
+
+pub static EN: Strings = Strings {
+    state_design: "Designing",
+    state_whiteboard: "Whiteboarding",
+    state_slides: "Presenting",
+    state_sites: "Building a site",
+    state_buzz: "Buzzing",
+    state_make: "Making",
+    state_dev_mode: "Dev Mode",
+    state_other: "Working",
+    state_idle: "Idle",
+
+    file_details: "File: {}",
+    unknown_file: "Unknown",
+    untitled: "Untitled",
+
+    figma_connecting: "Figma: Connecting...",
+    figma_connected: "Figma: Connected",
+    figma_disconnected: "Figma: Disconnected",
+    discord_connecting: "Discord: Connecting...",
+    discord_connected: "Discord: Connected",
+    discord_disconnected: "Discord: Disconnected",
+    privacy_on: "Privacy Mode: On ({})",
+    privacy_off: "Privacy Mode: Off",
+    presence_paused: "Presence: {}",
+    presence_schedule_off: "Presence: Off (Schedule)",
+    presence_schedule_private: "Presence: Private (Schedule)",
+    presence_active: "Presence: Active",
+    menu_pause: "Pause",
+    pause_15_minutes: "For 15 Minutes",
+    pause_1_hour: "For 1 Hour",
+    pause_until_tomorrow: "Until Tomorrow",
+    pause_until_resumed: "Until Resumed",
+    menu_resume: "Resume",
+    menu_profiles: "Profiles",
+    menu_settings: "Settings...",
+    menu_quit: "Quit",
+    menu_recent_files: "Recent Files",
+    file_hide_name: "Always Hide File Name",
+    file_set_alias: "Set Alias...",
+    file_exclude: "Exclude From Tracking",
+    file_copy_link: "Copy Link",
+    tooltip_privacy: "Privacy mode ({})",
+    tooltip_no_file: "No file open",
+    paused: "Paused",
+    paused_until: "Paused until {}",
+    notify_discord_lost: "Disconnected from Discord",
+    notify_discord_lost_body: "Your activity is hidden until Discord is reachable again.",
+    notify_discord_back: "Reconnected to Discord",
+    notify_discord_back_body: "Your activity is shown again.",
+    notify_figma_error: "Can't read Figma's open files",
+    notify_settings_problem: "Settings problem",
+    notify_update: "Update available",
+    notify_update_body: "Version {} is available.",
+
+    schedule_enable: "Only show during",
+    schedule_disable: "Hide during",
+    schedule_privacy: "Privacy mode during",
+    weekdays: ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+
+    tab_general: "General",
+    tab_images: "Images",
+    tab_privacy: "Privacy",
+    tab_advanced: "Advanced",
+    tab_about: "About",
+    preview_playing: "PLAYING",
+    preview_elapsed: "{} elapsed",
+    preview_example: "No Figma file open, showing an example.",
+    preview_privacy_cleared: "Privacy mode is on, so Discord shows no activity.",
+    preview_load_failed: "Couldn't load \"{}\"",
+    profile: "Profile",
+    profile_new: "New",
+    profile_duplicate: "Duplicate",
+    profile_delete: "Delete",
+    profile_name: "Profile name",
+    profile_rename: "Rename",
+    profile_copy: "{} (copy)",
+    app_name: "App Name",
+    app_name_custom: "Custom",
+    app_name_hint: "App name",
+    other_settings: "Other Settings",
+    hide_file_names: "Hide File Names",
+    disable_idle: "Disable Idle Detection",
+    start_on_login: "Start on Login",
+    appearance: "Appearance",
+    theme: "Theme",
+    theme_system: "Follow System",
+    theme_light: "Light",
+    theme_dark: "Dark",
+    interface_size: "Interface size",
+    language: "Language",
+    language_system: "System ({})",
+    shortcuts_hint: "Ctrl+S saves, Esc closes, Ctrl+PageUp/PageDown switches tabs",
+    activity_images: "Activity Images",
+    default_image: "Default image (URL or Discord asset key)",
+    default_image_hint: "Default Figma Icon",
+    image_hint: "Image URL or asset key",
+    image_asset_key: "Discord asset key",
+    image_url: "Image URL",
+    image_checking: "Checking...",
+    image_reachable: "Image URL, reachable",
+    image_too_long: "Must be at most {} characters",
+    image_whitespace: "Must not contain spaces",
+    image_scheme: "{}:// links aren't supported, use http(s)",
+    image_no_host: "URL has no host",
+    image_invalid: "Not an http(s) URL or Discord asset key",
+    check_images: "Check Images",
+    check_images_hover: "Ask each server whether the link points to an image",
+    privacy_mode: "Privacy Mode",
+    privacy_enable: "Enable while screen sharing or in meetings",
+    privacy_clear: "Clear Presence",
+    privacy_processes: "Processes (one per line)",
+    files: "Files",
+    files_hint: "Add rules for single files from the tray's Recent Files menu.",
+    file_alias: "Alias",
+    schedule: "Schedule",
+    schedule_rule_enabled: "Rule enabled",
+    schedule_start: "Start",
+    schedule_end: "End",
+    schedule_to: "to",
+    hour: "hour",
+    minute: "minute",
+    remove: "Remove",
+    add_rule: "Add Rule",
+    advanced: "Advanced",
+    figma_polling: "Figma polling",
+    discord_updates: "Discord updates",
+    idle_after: "Idle after",
+    battery_saver: "Battery saver (poll {}x less often on battery)",
+    notifications: "Notifications",
+    notify_enabled: "Show desktop notifications",
+    notify_discord: "Discord connection",
+    notify_figma: "Problems reading Figma",
+    notify_settings: "Settings problems",
+    notify_updates: "Available updates",
+    notify_interval: "Repeat at most every",
+    already_running: "Figma Rich Presence is already running",
+    import_export: "Import / Export",
+    settings_file: "Settings file",
+    export: "Export",
+    exported_to: "Exported to {}",
+    import: "Import...",
+    include_personal: "Include personal settings",
+    import_no_changes: "The file matches your current settings.",
+    import_changes: "Importing will change {} setting(s):",
+    apply: "Apply",
+    discard: "Discard",
+    imported: "Imported, click Save to keep the changes",
+    version: "Version {}",
+    settings_folder: "Settings folder",
+    copy: "Copy",
+    managed_by: "{} setting(s) are managed by {}",
+    managed_notice: "Some settings are managed by your organization.",
+    save: "Save",
+    cancel: "Cancel",
+    reset_defaults: "Reset to Defaults",
+    unsaved_changes: "Unsaved changes",
+    unsaved_title: "Unsaved Changes",
+    unsaved_prompt: "Save your changes before closing?",
+    keep_editing: "Keep Editing",
+    save_failed: "Failed to save settings: {}",
+    notice_reset: "Settings were reset, backup saved as {}",
+    notice_unreadable: "Settings could not be read, using defaults",
+    notice_newer_version: "Settings from a newer version were backed up as {}",
+    notice_invalid_edit: "Ignored invalid settings edit: {}",
+};
+
+pub static DE: Strings = Strings {
+    state_design: "Gestaltet",
+    state_whiteboard: "Am Whiteboard",
+    state_slides: "Präsentiert",
+    state_sites: "Baut eine Website",
+    state_buzz: "In Buzz",
+    state_make: "In Make",
+    state_dev_mode: "Dev Mode",
+    state_other: "Arbeitet",
+    state_idle: "Inaktiv",
+
+    file_details: "Datei: {}",
+    unknown_file: "Unbekannt",
+    untitled: "Unbenannt",
+
+    figma_connecting: "Figma: Verbinde...",
+    figma_connected: "Figma: Verbunden",
+    figma_disconnected: "Figma: Getrennt",
+    discord_connecting: "Discord: Verbinde...",
+    discord_connected: "Discord: Verbunden",
+    discord_disconnected: "Discord: Getrennt",
+    privacy_on: "Privatsphäre-Modus: An ({})",
+    privacy_off: "Privatsphäre-Modus: Aus",
+    presence_paused: "Status: {}",
+    presence_schedule_off: "Status: Aus (Zeitplan)",
+    presence_schedule_private: "Status: Privat (Zeitplan)",
+    presence_active: "Status: Aktiv",
+    menu_pause: "Pausieren",
+    pause_15_minutes: "Für 15 Minuten",
+    pause_1_hour: "Für 1 Stunde",
+    pause_until_tomorrow: "Bis morgen",
+    pause_until_resumed: "Bis zum Fortsetzen",
+    menu_resume: "Fortsetzen",
+    menu_profiles: "Profile",
+    menu_settings: "Einstellungen...",
+    menu_quit: "Beenden",
+    menu_recent_files: "Zuletzt geöffnet",
+    file_hide_name: "Dateinamen immer ausblenden",
+    file_set_alias: "Alias festlegen...",
+    file_exclude: "Nicht erfassen",
+    file_copy_link: "Link kopieren",
+    tooltip_privacy: "Privatsphäre-Modus ({})",
+    tooltip_no_file: "Keine Datei geöffnet",
+    paused: "Pausiert",
+    paused_until: "Pausiert bis {}",
+    notify_discord_lost: "Verbindung zu Discord getrennt",
+    notify_discord_lost_body: "Deine Aktivität ist verborgen, bis Discord wieder erreichbar ist.",
+    notify_discord_back: "Wieder mit Discord verbunden",
+    notify_discord_back_body: "Deine Aktivität wird wieder angezeigt.",
+    notify_figma_error: "Geöffnete Figma-Dateien nicht lesbar",
+    notify_settings_problem: "Problem mit den Einstellungen",
+    notify_update: "Update verfügbar",
+    notify_update_body: "Version {} ist verfügbar.",
+
+    schedule_enable: "Nur anzeigen während",
+    schedule_disable: "Ausblenden während",
+    schedule_privacy: "Privatsphäre-Modus während",
+    weekdays: ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"],
+
+    tab_general: "Allgemein",
+    tab_images: "Bilder",
+    tab_privacy: "Privatsphäre",
+    tab_advanced: "Erweitert",
+    tab_about: "Über",
+    preview_playing: "SPIELT",
+    preview_elapsed: "{} vergangen",
+    preview_example: "Keine Figma-Datei geöffnet, ein Beispiel wird angezeigt.",
+    preview_privacy_cleared: "Der Privatsphäre-Modus ist aktiv, Discord zeigt keine Aktivität an.",
+    preview_load_failed: "\"{}\" konnte nicht geladen werden",
+    profile: "Profil",
+    profile_new: "Neu",
+    profile_duplicate: "Duplizieren",
+    profile_delete: "Löschen",
+    profile_name: "Profilname",
+    profile_rename: "Umbenennen",
+    profile_copy: "{} (Kopie)",
+    app_name: "App-Name",
+    app_name_custom: "Eigener",
+    app_name_hint: "App-Name",
+    other_settings: "Weitere Einstellungen",
+    hide_file_names: "Dateinamen ausblenden",
+    disable_idle: "Inaktivitätserkennung deaktivieren",
+    start_on_login: "Bei Anmeldung starten",
+    appearance: "Darstellung",
+    theme: "Design",
+    theme_system: "Wie System",
+    theme_light: "Hell",
+    theme_dark: "Dunkel",
+    interface_size: "Größe der Oberfläche",
+    language: "Sprache",
+    language_system: "System ({})",
+    shortcuts_hint: "Strg+S speichert, Esc schließt, Strg+Bild↑/Bild↓ wechselt Tabs",
+    activity_images: "Aktivitätsbilder",
+    default_image: "Standardbild (URL oder Discord-Asset-Schlüssel)",
+    default_image_hint: "Standard-Figma-Symbol",
+    image_hint: "Bild-URL oder Asset-Schlüssel",
+    image_asset_key: "Discord-Asset-Schlüssel",
+    image_url: "Bild-URL",
+    image_checking: "Wird geprüft...",
+    image_reachable: "Bild-URL, erreichbar",
+    image_too_long: "Höchstens {} Zeichen erlaubt",
+    image_whitespace: "Darf keine Leerzeichen enthalten",
+    image_scheme: "{}://-Links werden nicht unterstützt, bitte http(s) verwenden",
+    image_no_host: "URL hat keinen Host",
+    image_invalid: "Weder http(s)-URL noch Discord-Asset-Schlüssel",
+    check_images: "Bilder prüfen",
+    check_images_hover: "Bei jedem Server nachfragen, ob der Link auf ein Bild zeigt",
+    privacy_mode: "Privatsphäre-Modus",
+    privacy_enable: "Beim Bildschirmteilen oder in Meetings aktivieren",
+    privacy_clear: "Status ausblenden",
+    privacy_processes: "Prozesse (einer pro Zeile)",
+    files: "Dateien",
+    files_hint: "Regeln für einzelne Dateien lassen sich im Tray-Menü unter Zuletzt geöffnet anlegen.",
+    file_alias: "Alias",
+    schedule: "Zeitplan",
+    schedule_rule_enabled: "Regel aktiv",
+    schedule_start: "Beginn",
+    schedule_end: "Ende",
+    schedule_to: "bis",
+    hour: "Stunde",
+    minute: "Minute",
+    remove: "Entfernen",
+    add_rule: "Regel hinzufügen",
+    advanced: "Erweitert",
+    figma_polling: "Figma-Abfrage",
+    discord_updates: "Discord-Aktualisierung",
+    idle_after: "Inaktiv nach",
+    battery_saver: "Akku sparen ({}x seltener abfragen im Akkubetrieb)",
+    notifications: "Benachrichtigungen",
+    notify_enabled: "Desktop-Benachrichtigungen anzeigen",
+    notify_discord: "Discord-Verbindung",
+    notify_figma: "Probleme beim Lesen von Figma",
+    notify_settings: "Probleme mit den Einstellungen",
+    notify_updates: "Verfügbare Updates",
+    notify_interval: "Höchstens wiederholen alle",
+    already_running: "Figma Rich Presence läuft bereits",
+    import_export: "Import / Export",
+    settings_file: "Einstellungsdatei",
+    export: "Exportieren",
+    exported_to: "Nach {} exportiert",
+    import: "Importieren...",
+    include_personal: "Persönliche Einstellungen einschließen",
+    import_no_changes: "Die Datei entspricht deinen aktuellen Einstellungen.",
+    import_changes: "Der Import ändert {} Einstellung(en):",
+    apply: "Übernehmen",
+    discard: "Verwerfen",
+    imported: "Importiert, zum Behalten auf Speichern klicken",
+    version: "Version {}",
+    settings_folder: "Einstellungsordner",
+    copy: "Kopieren",
+    managed_by: "{} Einstellung(en) werden von {} verwaltet",
+    managed_notice: "Einige Einstellungen werden von deiner Organisation verwaltet.",
+    save: "Speichern",
+    cancel: "Abbrechen",
+    reset_defaults: "Auf Standard zurücksetzen",
+    unsaved_changes: "Ungespeicherte Änderungen",
+    unsaved_title: "Ungespeicherte Änderungen",
+    unsaved_prompt: "Änderungen vor dem Schließen speichern?",
+    keep_editing: "Weiter bearbeiten",
+    save_failed: "Einstellungen konnten nicht gespeichert werden: {}",
+    notice_reset: "Einstellungen wurden zurückgesetzt, Sicherung gespeichert als {}",
+    notice_unreadable: "Einstellungen konnten nicht gelesen werden, Standardwerte werden verwendet",
+    notice_newer_version: "Einstellungen einer neueren Version wurden gesichert als {}",
+    notice_invalid_edit: "Ungültige Änderung der Einstellungen ignoriert: {}",
+};
+
+pub static FR: Strings = Strings {
+    state_design: "Conçoit",
+    state_whiteboard: "Sur le tableau blanc",
+    state_slides: "Présente",
+    state_sites: "Crée un site",
+    state_buzz: "Dans Buzz",
+    state_make: "Dans Make",
+    state_dev_mode: "Dev Mode",
+    state_other: "Travaille",
+    state_idle: "Inactif",
+
+    file_details: "Fichier : {}",
+    unknown_file: "Inconnu",
+    untitled: "Sans titre",
+
+    figma_connecting: "Figma : connexion...",
+    figma_connected: "Figma : connecté",
+    figma_disconnected: "Figma : déconnecté",
+    discord_connecting: "Discord : connexion...",
+    discord_connected: "Discord : connecté",
+    discord_disconnected: "Discord : déconnecté",
+    privacy_on: "Mode confidentialité : activé ({})",
+    privacy_off: "Mode confidentialité : désactivé",
+    presence_paused: "Présence : {}",
+    presence_schedule_off: "Présence : désactivée (planning)",
+    presence_schedule_private: "Présence : privée (planning)",
+    presence_active: "Présence : active",
+    menu_pause: "Mettre en pause",
+    pause_15_minutes: "Pendant 15 minutes",
+    pause_1_hour: "Pendant 1 heure",
+    pause_until_tomorrow: "Jusqu'à demain",
+    pause_until_resumed: "Jusqu'à la reprise",
+    menu_resume: "Reprendre",
+    menu_profiles: "Profils",
+    menu_settings: "Paramètres...",
+    menu_quit: "Quitter",
+    menu_recent_files: "Fichiers récents",
+    file_hide_name: "Toujours masquer le nom du fichier",
+    file_set_alias: "Définir un alias...",
+    file_exclude: "Exclure du suivi",
+    file_copy_link: "Copier le lien",
+    tooltip_privacy: "Mode confidentialité ({})",
+    tooltip_no_file: "Aucun fichier ouvert",
+    paused: "En pause",
+    paused_until: "En pause jusqu'à {}",
+    notify_discord_lost: "Déconnecté de Discord",
+    notify_discord_lost_body: "Votre activité est masquée jusqu'au retour de Discord.",
+    notify_discord_back: "Reconnecté à Discord",
+    notify_discord_back_body: "Votre activité est de nouveau affichée.",
+    notify_figma_error: "Impossible de lire les fichiers ouverts dans Figma",
+    notify_settings_problem: "Problème de paramètres",
+    notify_update: "Mise à jour disponible",
+    notify_update_body: "La version {} est disponible.",
+
+    schedule_enable: "Afficher seulement pendant",
+    schedule_disable: "Masquer pendant",
+    schedule_privacy: "Mode confidentialité pendant",
+    weekdays: ["Lun", "Mar", "Mer", "Jeu", "Ven", "Sam", "Dim"],
+
+    tab_general: "Général",
+    tab_images: "Images",
+    tab_privacy: "Confidentialité",
+    tab_advanced: "Avancé",
+    tab_about: "À propos",
+    preview_playing: "JOUE À",
+    preview_elapsed: "{} écoulé",
+    preview_example: "Aucun fichier Figma ouvert, voici un exemple.",
+    preview_privacy_cleared: "Le mode confidentialité est actif, Discord n'affiche aucune activité.",
+    preview_load_failed: "Impossible de charger « {} »",
+    profile: "Profil",
+    profile_new: "Nouveau",
+    profile_duplicate: "Dupliquer",
+    profile_delete: "Supprimer",
+    profile_name: "Nom du profil",
+    profile_rename: "Renommer",
+    profile_copy: "{} (copie)",
+    app_name: "Nom de l'application",
+    app_name_custom: "Personnalisé",
+    app_name_hint: "Nom de l'application",
+    other_settings: "Autres paramètres",
+    hide_file_names: "Masquer les noms de fichiers",
+    disable_idle: "Désactiver la détection d'inactivité",
+    start_on_login: "Lancer à l'ouverture de session",
+    appearance: "Apparence",
+    theme: "Thème",
+    theme_system: "Suivre le système",
+    theme_light: "Clair",
+    theme_dark: "Sombre",
+    interface_size: "Taille de l'interface",
+    language: "Langue",
+    language_system: "Système ({})",
+    shortcuts_hint: "Ctrl+S enregistre, Échap ferme, Ctrl+Page préc./suiv. change d'onglet",
+    activity_images: "Images d'activité",
+    default_image: "Image par défaut (URL ou clé d'asset Discord)",
+    default_image_hint: "Icône Figma par défaut",
+    image_hint: "URL d'image ou clé d'asset",
+    image_asset_key: "Clé d'asset Discord",
+    image_url: "URL d'image",
+    image_checking: "Vérification...",
+    image_reachable: "URL d'image, accessible",
+    image_too_long: "{} caractères maximum",
+    image_whitespace: "Ne doit pas contenir d'espaces",
+    image_scheme: "Les liens {}:// ne sont pas pris en charge, utilisez http(s)",
+    image_no_host: "L'URL n'a pas d'hôte",
+    image_invalid: "Ni une URL http(s) ni une clé d'asset Discord",
+    check_images: "Vérifier les images",
+    check_images_hover: "Demander à chaque serveur si le lien pointe vers une image",
+    privacy_mode: "Mode confidentialité",
+    privacy_enable: "Activer pendant le partage d'écran ou les réunions",
+    privacy_clear: "Effacer la présence",
+    privacy_processes: "Processus (un par ligne)",
+    files: "Fichiers",
+    files_hint: "Ajoutez des règles pour un fichier depuis le menu Fichiers récents de l'icône.",
+    file_alias: "Alias",
+    schedule: "Planning",
+    schedule_rule_enabled: "Règle active",
+    schedule_start: "Début",
+    schedule_end: "Fin",
+    schedule_to: "à",
+    hour: "heure",
+    minute: "minute",
+    remove: "Retirer",
+    add_rule: "Ajouter une règle",
+    advanced: "Avancé",
+    figma_polling: "Interrogation de Figma",
+    discord_updates: "Mises à jour Discord",
+    idle_after: "Inactif après",
+    battery_saver: "Économie de batterie (interroger {}x moins souvent sur batterie)",
+    notifications: "Notifications",
+    notify_enabled: "Afficher les notifications du bureau",
+    notify_discord: "Connexion à Discord",
+    notify_figma: "Problèmes de lecture de Figma",
+    notify_settings: "Problèmes de paramètres",
+    notify_updates: "Mises à jour disponibles",
+    notify_interval: "Répéter au plus toutes les",
+    already_running: "Figma Rich Presence est déjà lancé",
+    import_export: "Importer / Exporter",
+    settings_file: "Fichier de paramètres",
+    export: "Exporter",
+    exported_to: "Exporté vers {}",
+    import: "Importer...",
+    include_personal: "Inclure les paramètres personnels",
+    import_no_changes: "Le fichier correspond à vos paramètres actuels.",
+    import_changes: "L'import modifiera {} paramètre(s) :",
+    apply: "Appliquer",
+    discard: "Ignorer",
+    imported: "Importé, cliquez sur Enregistrer pour conserver les modifications",
+    version: "Version {}",
+    settings_folder: "Dossier des paramètres",
+    copy: "Copier",
+    managed_by: "{} paramètre(s) sont gérés par {}",
+    managed_notice: "Certains paramètres sont gérés par votre organisation.",
+    save: "Enregistrer",
+    cancel: "Annuler",
+    reset_defaults: "Rétablir les valeurs par défaut",
+    unsaved_changes: "Modifications non enregistrées",
+    unsaved_title: "Modifications non enregistrées",
+    unsaved_prompt: "Enregistrer les modifications avant de fermer ?",
+    keep_editing: "Continuer",
+    save_failed: "Impossible d'enregistrer les paramètres : {}",
+    notice_reset: "Les paramètres ont été réinitialisés, sauvegarde enregistrée sous {}",
+    notice_unreadable: "Impossible de lire les paramètres, valeurs par défaut utilisées",
+    notice_newer_version: "Les paramètres d'une version plus récente ont été sauvegardés sous {}",
+    notice_invalid_edit: "Modification invalide des paramètres ignorée : {}",
+};
+
+pub static ES: Strings = Strings {
+    state_design: "Diseñando",
+    state_whiteboard: "En la pizarra",
+    state_slides: "Presentando",
+    state_sites: "Creando un sitio",
+    state_buzz: "En Buzz",
+    state_make: "En Make",
+    state_dev_mode: "Dev Mode",
+    state_other: "Trabajando",
+    state_idle: "Inactivo",
+
+    file_details: "Archivo: {}",
+    unknown_file: "Desconocido",
+    untitled: "Sin título",
+
+    figma_connecting: "Figma: Conectando...",
+    figma_connected: "Figma: Conectado",
+    figma_disconnected: "Figma: Desconectado",
+    discord_connecting: "Discord: Conectando...",
+    discord_connected: "Discord: Conectado",
+    discord_disconnected: "Discord: Desconectado",
+    privacy_on: "Modo privado: Activado ({})",
+    privacy_off: "Modo privado: Desactivado",
+    presence_paused: "Presencia: {}",
+    presence_schedule_off: "Presencia: Desactivada (horario)",
+    presence_schedule_private: "Presencia: Privada (horario)",
+    presence_active: "Presencia: Activa",
+    menu_pause: "Pausar",
+    pause_15_minutes: "Durante 15 minutos",
+    pause_1_hour: "Durante 1 hora",
+    pause_until_tomorrow: "Hasta mañana",
+    pause_until_resumed: "Hasta reanudar",
+    menu_resume: "Reanudar",
+    menu_profiles: "Perfiles",
+    menu_settings: "Ajustes...",
+    menu_quit: "Salir",
+    menu_recent_files: "Archivos recientes",
+    file_hide_name: "Ocultar siempre el nombre del archivo",
+    file_set_alias: "Definir alias...",
+    file_exclude: "Excluir del seguimiento",
+    file_copy_link: "Copiar enlace",
+    tooltip_privacy: "Modo privado ({})",
+    tooltip_no_file: "Ningún archivo abierto",
+    paused: "En pausa",
+    paused_until: "En pausa hasta {}",
+    notify_discord_lost: "Desconectado de Discord",
+    notify_discord_lost_body: "Tu actividad está oculta hasta que Discord vuelva a estar disponible.",
+    notify_discord_back: "Reconectado a Discord",
+    notify_discord_back_body: "Tu actividad se muestra de nuevo.",
+    notify_figma_error: "No se pueden leer los archivos abiertos en Figma",
+    notify_settings_problem: "Problema con la configuración",
+    notify_update: "Actualización disponible",
+    notify_update_body: "La versión {} está disponible.",
+
+    schedule_enable: "Mostrar solo durante",
+    schedule_disable: "Ocultar durante",
+    schedule_privacy: "Modo privado durante",
+    weekdays: ["Lun", "Mar", "Mié", "Jue", "Vie", "Sáb", "Dom"],
+
+    tab_general: "General",
+    tab_images: "Imágenes",
+    tab_privacy: "Privacidad",
+    tab_advanced: "Avanzado",
+    tab_about: "Acerca de",
+    preview_playing: "JUGANDO A",
+    preview_elapsed: "{} transcurrido",
+    preview_example: "No hay ningún archivo de Figma abierto, se muestra un ejemplo.",
+    preview_privacy_cleared: "El modo privacidad está activo, Discord no muestra ninguna actividad.",
+    preview_load_failed: "No se pudo cargar \"{}\"",
+    profile: "Perfil",
+    profile_new: "Nuevo",
+    profile_duplicate: "Duplicar",
+    profile_delete: "Eliminar",
+    profile_name: "Nombre del perfil",
+    profile_rename: "Renombrar",
+    profile_copy: "{} (copia)",
+    app_name: "Nombre de la aplicación",
+    app_name_custom: "Personalizado",
+    app_name_hint: "Nombre de la aplicación",
+    other_settings: "Otros ajustes",
+    hide_file_names: "Ocultar nombres de archivo",
+    disable_idle: "Desactivar detección de inactividad",
+    start_on_login: "Iniciar al iniciar sesión",
+    appearance: "Apariencia",
+    theme: "Tema",
+    theme_system: "Según el sistema",
+    theme_light: "Claro",
+    theme_dark: "Oscuro",
+    interface_size: "Tamaño de la interfaz",
+    language: "Idioma",
+    language_system: "Sistema ({})",
+    shortcuts_hint: "Ctrl+S guarda, Esc cierra, Ctrl+RePág/AvPág cambia de pestaña",
+    activity_images: "Imágenes de actividad",
+    default_image: "Imagen predeterminada (URL o clave de recurso de Discord)",
+    default_image_hint: "Icono de Figma predeterminado",
+    image_hint: "URL de imagen o clave de recurso",
+    image_asset_key: "Clave de recurso de Discord",
+    image_url: "URL de imagen",
+    image_checking: "Comprobando...",
+    image_reachable: "URL de imagen, accesible",
+    image_too_long: "Como máximo {} caracteres",
+    image_whitespace: "No debe contener espacios",
+    image_scheme: "Los enlaces {}:// no son compatibles, usa http(s)",
+    image_no_host: "La URL no tiene host",
+    image_invalid: "No es una URL http(s) ni una clave de recurso de Discord",
+    check_images: "Comprobar imágenes",
+    check_images_hover: "Preguntar a cada servidor si el enlace apunta a una imagen",
+    privacy_mode: "Modo privado",
+    privacy_enable: "Activar al compartir pantalla o en reuniones",
+    privacy_clear: "Borrar presencia",
+    privacy_processes: "Procesos (uno por línea)",
+    files: "Archivos",
+    files_hint: "Añade reglas para un archivo desde el menú Archivos recientes de la bandeja.",
+    file_alias: "Alias",
+    schedule: "Horario",
+    schedule_rule_enabled: "Regla activa",
+    schedule_start: "Inicio",
+    schedule_end: "Fin",
+    schedule_to: "a",
+    hour: "hora",
+    minute: "minuto",
+    remove: "Quitar",
+    add_rule: "Añadir regla",
+    advanced: "Avanzado",
+    figma_polling: "Consulta de Figma",
+    discord_updates: "Actualizaciones de Discord",
+    idle_after: "Inactivo tras",
+    battery_saver: "Ahorro de batería (consultar {}x menos con batería)",
+    notifications: "Notificaciones",
+    notify_enabled: "Mostrar notificaciones de escritorio",
+    notify_discord: "Conexión con Discord",
+    notify_figma: "Problemas al leer Figma",
+    notify_settings: "Problemas con la configuración",
+    notify_updates: "Actualizaciones disponibles",
+    notify_interval: "Repetir como máximo cada",
+    already_running: "Figma Rich Presence ya se está ejecutando",
+    import_export: "Importar / Exportar",
+    settings_file: "Archivo de ajustes",
+    export: "Exportar",
+    exported_to: "Exportado a {}",
+    import: "Importar...",
+    include_personal: "Incluir ajustes personales",
+    import_no_changes: "El archivo coincide con tus ajustes actuales.",
+    import_changes: "Importar cambiará {} ajuste(s):",
+    apply: "Aplicar",
+    discard: "Descartar",
+    imported: "Importado, pulsa Guardar para conservar los cambios",
+    version: "Versión {}",
+    settings_folder: "Carpeta de ajustes",
+    copy: "Copiar",
+    managed_by: "{} ajuste(s) están gestionados por {}",
+    managed_notice: "Algunos ajustes están gestionados por tu organización.",
+    save: "Guardar",
+    cancel: "Cancelar",
+    reset_defaults: "Restablecer valores predeterminados",
+    unsaved_changes: "Cambios sin guardar",
+    unsaved_title: "Cambios sin guardar",
+    unsaved_prompt: "¿Guardar los cambios antes de cerrar?",
+    keep_editing: "Seguir editando",
+    save_failed: "No se pudieron guardar los ajustes: {}",
+    notice_reset: "Se restablecieron los ajustes, copia guardada como {}",
+    notice_unreadable: "No se pudieron leer los ajustes, se usan los valores predeterminados",
+    notice_newer_version: "Los ajustes de una versión más reciente se guardaron como {}",
+    notice_invalid_edit: "Se ignoró un cambio no válido en los ajustes: {}",
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    /// Every string in `strings`, keyed by field name (and index for lists).
+    fn entries(strings: &Strings) -> Vec<(String, String)> {
+        let Value::Object(fields) = serde_json::to_value(strings).unwrap() else {
+            unreachable!();
+        };
+        let mut out = Vec::new();
+        for (key, value) in fields {
+            match value {
+                Value::String(text) => out.push((key, text)),
+                Value::Array(items) => {
+                    for (i, item) in items.into_iter().enumerate() {
+                        out.push((format!("{key}[{i}]"), item.as_str().unwrap().to_string()));
+                    }
+                }
+                other => panic!("unexpected value for {key}: {other}"),
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn translations_match_english() {
+        let english = entries(&EN);
+        for language in [Language::German, Language::French, Language::Spanish] {
+            let translated = entries(language.strings());
+            assert_eq!(translated.len(), english.len());
+            for ((key, en), (_, text)) in english.iter().zip(&translated) {
+                assert!(!text.is_empty(), "{language:?} {key} is empty");
+                assert_eq!(
+                    text.matches("{}").count(),
+                    en.matches("{}").count(),
+                    "{language:?} {key} has a different number of placeholders",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn from_locale_reads_the_language_code() {
+        assert_eq!(Language::from_locale("de-DE"), Some(Language::German));
+        assert_eq!(Language::from_locale("fr_CA"), Some(Language::French));
+        assert_eq!(Language::from_locale("es"), Some(Language::Spanish));
+        assert_eq!(Language::from_locale("EN-us"), Some(Language::English));
+        assert_eq!(Language::from_locale("ja-JP"), None);
+        assert_eq!(Language::from_locale(""), None);
+    }
+}
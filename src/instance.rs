@@ -0,0 +1,444 @@
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Local};
+use interprocess::local_socket::{ListenerOptions, Name, Stream, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    io::{BufRead, BufReader, Write},
+    sync::{
+        Arc, OnceLock, RwLock,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Sender},
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    figma::FigmaState,
+    log_debug, log_info, log_warn,
+    pause::{self, PauseDuration, PauseState},
+    presence::{self, PresenceContent},
+    schedule::{self, ScheduleOutcome},
+    settings::{self, PrivacyAction, Settings},
+};
+
+/// Held for the lifetime of the process, released by the OS on exit.
+static LOCK: OnceLock<File> = OnceLock::new();
+
+/// An instance that was just restarted by the updater may briefly overlap
+/// with the one it replaces, so the lock is retried for a while.
+const LOCK_ATTEMPTS: u32 = 20;
+const LOCK_RETRY: Duration = Duration::from_millis(100);
+
+/// How long a request waits for the tray to handle it.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Requests to the running instance, from later launches handing off or
+/// from the control commands. Sent as one JSON object per line, each
+/// answered with one [`Reply`] line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Command {
+    OpenSettings,
+    ShowStatus,
+    Status,
+    /// `None` pauses until resumed.
+    Pause {
+        #[serde(default)]
+        minutes: Option<u32>,
+    },
+    Resume,
+    Reload,
+    Quit,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reply {
+    Done,
+    Status(Status),
+    Error(String),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Status {
+    pub version: String,
+    pub profile: String,
+    pub discord_connected: bool,
+    pub figma_connected: bool,
+    /// The file name as Discord shows it, `None` when it's hidden or no
+    /// activity is shown.
+    pub file: Option<String>,
+    /// State key of the activity shown, e.g. `design` or `idle`.
+    pub activity: Option<String>,
+    pub paused: bool,
+    /// `None` while paused means until resumed.
+    pub paused_until: Option<DateTime<Local>>,
+    /// The process that switched on privacy mode.
+    pub privacy: Option<String>,
+}
+
+/// The state the tray shares with the tracking threads, which most control
+/// commands read or change.
+pub struct Shared {
+    pub running: Arc<AtomicBool>,
+    pub settings: Arc<RwLock<Settings>>,
+    pub figma_state: Arc<RwLock<FigmaState>>,
+    pub figma_connected: Arc<AtomicBool>,
+    pub discord_connected: Arc<AtomicBool>,
+    pub privacy_active: Arc<RwLock<Option<String>>>,
+    pub pause_state: Arc<RwLock<PauseState>>,
+}
+
+impl Shared {
+    /// Carries out `command` when the shared state is all it needs. Opening
+    /// windows and quitting are left to the tray, which gets `None`.
+    pub fn handle(&self, command: Command) -> Option<Reply> {
+        let reply = match command {
+            Command::Status => Reply::Status(self.status()),
+            Command::Pause { minutes: Some(0) } => {
+                Reply::Error("pause must last at least a minute".to_string())
+            }
+            Command::Pause { minutes } => {
+                self.set_paused(match minutes {
+                    Some(minutes) => PauseState::for_minutes(minutes),
+                    None => PauseState::start(PauseDuration::UntilResumed),
+                });
+                Reply::Done
+            }
+            Command::Resume => {
+                pause::persist(PauseState::Active);
+                self.set_paused(PauseState::Active);
+                Reply::Done
+            }
+            Command::Reload => match settings::reload(&self.settings) {
+                Ok(changed) => {
+                    if changed {
+                        log_info!("settings", "Reloaded on request");
+                    }
+                    Reply::Done
+                }
+                Err(e) => Reply::Error(format!("{e:#}")),
+            },
+            Command::OpenSettings | Command::ShowStatus | Command::Quit => return None,
+        };
+        Some(reply)
+    }
+
+    pub fn set_paused(&self, state: PauseState) {
+        let strings = self.settings.read().unwrap().strings();
+        if let Some(text) = state.describe(strings) {
+            log_info!("pause", "{text}");
+        } else {
+            log_info!("pause", "Resumed");
+        }
+        *self.pause_state.write().unwrap() = state;
+    }
+
+    /// Reports the file and activity only as far as Discord is shown them,
+    /// so excluded files, hidden names and privacy mode hold here too.
+    fn status(&self) -> Status {
+        let settings = self.settings.read().unwrap();
+        let figma = self.figma_state.read().unwrap();
+        let pause_state = *self.pause_state.read().unwrap();
+        let privacy = self.privacy_active.read().unwrap().clone();
+        let schedule = schedule::evaluate(&settings.schedule, Local::now().naive_local());
+        let privacy_on = privacy.is_some() || schedule == ScheduleOutcome::Privacy;
+        let cleared = privacy_on && settings.privacy.action == PrivacyAction::ClearPresence;
+        let content = figma
+            .active_tab
+            .as_ref()
+            .filter(|tab| !cleared && !presence::is_excluded(&settings, tab))
+            .map(|_| PresenceContent::build(&settings, &figma, privacy_on));
+        Status {
+            version: crate::VERSION.to_string(),
+            profile: settings.active_profile.clone(),
+            discord_connected: self.discord_connected.load(Ordering::Relaxed),
+            figma_connected: self.figma_connected.load(Ordering::Relaxed),
+            file: content.as_ref().and_then(|content| content.title.clone()),
+            activity: content.map(|content| content.state_key.to_string()),
+            paused: pause_state.is_paused(),
+            paused_until: match pause_state {
+                PauseState::Until(until) if pause_state.is_paused() => Some(until),
+                _ => None,
+            },
+            privacy,
+        }
+    }
+}
+
+/// A socket file on Unix, a named pipe on Windows, both tied to the config
+/// dir so portable copies and `--config-dir` users don't collide.
+fn socket_name() -> std::io::Result<Name<'static>> {
+    #[cfg(target_os = "windows")]
+    {
+        use interprocess::local_socket::GenericNamespaced;
+        use sha2::{Digest, Sha256};
+        let digest = Sha256::digest(settings::config_dir().to_string_lossy().as_bytes());
+        format!("figma-discord-rp-{}", &hex::encode(digest)[..16]).to_ns_name::<GenericNamespaced>()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        use interprocess::local_socket::GenericFilePath;
+        settings::config_dir()
+            .join("instance.sock")
+            .to_fs_name::<GenericFilePath>()
+    }
+}
+
+/// Takes the single-instance lock. Returns false when another instance
+/// already holds it.
+pub fn lock() -> Result<bool> {
+    let dir = settings::config_dir();
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let path = dir.join("instance.lock");
+    let file = File::create(&path).with_context(|| format!("failed to open {}", path.display()))?;
+    for attempt in 1..=LOCK_ATTEMPTS {
+        match file.try_lock() {
+            Ok(()) => {
+                let _ = LOCK.set(file);
+                return Ok(true);
+            }
+            Err(fs::TryLockError::WouldBlock) if attempt < LOCK_ATTEMPTS => {
+                thread::sleep(LOCK_RETRY);
+            }
+            Err(fs::TryLockError::WouldBlock) => return Ok(false),
+            Err(fs::TryLockError::Error(e)) => {
+                return Err(e).with_context(|| format!("failed to lock {}", path.display()));
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Reads one request from `conn`, has `on_command` handle it and writes
+/// back its reply.
+fn serve(conn: Stream, on_command: &(impl Fn(Command, Sender<Reply>) + Sync)) {
+    let mut conn = BufReader::new(conn);
+    let mut line = String::new();
+    if let Err(e) = conn.read_line(&mut line) {
+        log_warn!("instance", "Failed to read request: {e}");
+        return;
+    }
+    let reply = match serde_json::from_str::<Command>(&line) {
+        Ok(command) => {
+            log_debug!("instance", "Received {command:?}");
+            let (reply_tx, reply_rx) = mpsc::channel();
+            on_command(command, reply_tx);
+            reply_rx
+                .recv_timeout(REPLY_TIMEOUT)
+                .unwrap_or_else(|_| Reply::Error("the tray didn't respond".to_string()))
+        }
+        Err(e) => {
+            log_warn!("instance", "Ignoring malformed request: {e}");
+            Reply::Error(format!("malformed request: {e}"))
+        }
+    };
+    if let Ok(mut data) = serde_json::to_string(&reply) {
+        data.push('\n');
+        if let Err(e) = conn.get_mut().write_all(data.as_bytes()) {
+            log_warn!("instance", "Failed to send reply: {e}");
+        }
+    }
+}
+
+/// Serves requests on a background thread, passing each one to
+/// `on_command` along with where to send its reply. Only call this while
+/// holding the lock, since a leftover socket file is replaced.
+pub fn listen(on_command: impl Fn(Command, Sender<Reply>) + Send + Sync + 'static) -> Result<()> {
+    let listener = ListenerOptions::new()
+        .name(socket_name()?)
+        .try_overwrite(true)
+        .create_sync()
+        .context("failed to create the instance socket")?;
+    thread::spawn(move || {
+        // Scoped so a client that never sends its line can't hold up the
+        // others.
+        thread::scope(|scope| {
+            for conn in listener.incoming() {
+                match conn {
+                    Ok(conn) => {
+                        scope.spawn(|| serve(conn, &on_command));
+                    }
+                    Err(e) => {
+                        log_warn!("instance", "Failed to accept connection: {e}");
+                    }
+                }
+            }
+        });
+    });
+    Ok(())
+}
+
+/// Sends `command` to the running instance and waits for its reply.
+pub fn request(command: Command) -> Result<Reply> {
+    let conn = Stream::connect(socket_name()?).context("no running instance")?;
+    let mut conn = BufReader::new(conn);
+    let mut line = serde_json::to_string(&command)?;
+    line.push('\n');
+    conn.get_mut()
+        .write_all(line.as_bytes())
+        .context("failed to reach the running instance")?;
+    line.clear();
+    if conn.read_line(&mut line)? == 0 {
+        bail!("the running instance closed the connection");
+    }
+    serde_json::from_str(&line).context("malformed reply")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        figma::FigmaTab,
+        settings::{FileRule, test_env::TestEnv},
+    };
+    use chrono::Duration as ChronoDuration;
+
+    fn shared() -> Arc<Shared> {
+        Arc::new(Shared {
+            running: Arc::new(AtomicBool::new(true)),
+            settings: Arc::default(),
+            figma_state: Arc::default(),
+            figma_connected: Arc::new(AtomicBool::new(true)),
+            discord_connected: Arc::new(AtomicBool::new(true)),
+            privacy_active: Arc::default(),
+            pause_state: Arc::default(),
+        })
+    }
+
+    fn status(reply: Reply) -> Status {
+        match reply {
+            Reply::Status(status) => status,
+            other => panic!("expected a status, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn requests_reach_the_running_instance() {
+        let env = TestEnv::new();
+        let shared = shared();
+        let handler = Arc::clone(&shared);
+        listen(move |command, reply| {
+            if let Some(answer) = handler.handle(command) {
+                let _ = reply.send(answer);
+            }
+        })
+        .unwrap();
+
+        let idle = status(request(Command::Status).unwrap());
+        assert_eq!(idle.version, crate::VERSION);
+        assert!(!idle.paused);
+
+        // A pause until tomorrow left over from before is replaced.
+        pause::persist(PauseState::Until(Local::now() + ChronoDuration::hours(12)));
+        assert!(env.path().join("pause.json").exists());
+        assert!(matches!(
+            request(Command::Pause { minutes: None }).unwrap(),
+            Reply::Done
+        ));
+        assert!(!env.path().join("pause.json").exists());
+        let paused = status(request(Command::Status).unwrap());
+        assert!(paused.paused);
+        assert_eq!(paused.paused_until, None);
+
+        assert!(matches!(
+            request(Command::Pause { minutes: Some(30) }).unwrap(),
+            Reply::Done
+        ));
+        let until = status(request(Command::Status).unwrap())
+            .paused_until
+            .expect("pause should have an end");
+        assert!(until > Local::now() + ChronoDuration::minutes(29));
+
+        assert!(matches!(
+            request(Command::Pause { minutes: Some(0) }).unwrap(),
+            Reply::Error(_)
+        ));
+
+        assert!(matches!(request(Command::Resume).unwrap(), Reply::Done));
+        assert_eq!(*shared.pause_state.read().unwrap(), PauseState::Active);
+        assert!(!status(request(Command::Status).unwrap()).paused);
+
+        let edited = Settings {
+            hide_filename: true,
+            ..Default::default()
+        };
+        edited.save().unwrap();
+        assert!(matches!(request(Command::Reload).unwrap(), Reply::Done));
+        assert!(shared.settings.read().unwrap().hide_filename);
+
+        fs::write(env.path().join("settings.json"), "{ not json").unwrap();
+        assert!(matches!(request(Command::Reload).unwrap(), Reply::Error(_)));
+        assert!(shared.settings.read().unwrap().hide_filename);
+
+        let mut conn = BufReader::new(Stream::connect(socket_name().unwrap()).unwrap());
+        conn.get_mut()
+            .write_all(b"{\"command\":\"dance\"}\n")
+            .unwrap();
+        let mut line = String::new();
+        conn.read_line(&mut line).unwrap();
+        match serde_json::from_str(&line).unwrap() {
+            Reply::Error(message) => assert!(message.starts_with("malformed request")),
+            other => panic!("expected an error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn status_shows_only_what_discord_is_shown() {
+        let _env = TestEnv::new();
+        let shared = shared();
+        shared.figma_state.write().unwrap().active_tab = Some(FigmaTab {
+            title: Some("Salaries".to_string()),
+            path: Some("/design/abc123/Salaries".to_string()),
+            ..Default::default()
+        });
+        let status = || status(shared.handle(Command::Status).unwrap());
+        let set_rule = |rule: FileRule| {
+            shared.settings.write().unwrap().privacy.files = vec![FileRule {
+                file: "abc123".to_string(),
+                ..rule
+            }];
+        };
+
+        let shown = status();
+        assert_eq!(shown.file.as_deref(), Some("Salaries"));
+        assert!(shown.activity.is_some());
+
+        set_rule(FileRule {
+            hide_name: true,
+            ..Default::default()
+        });
+        let hidden = status();
+        assert_eq!(hidden.file, None);
+        assert!(hidden.activity.is_some());
+
+        set_rule(FileRule {
+            alias: "Budget".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(status().file.as_deref(), Some("Budget"));
+
+        set_rule(FileRule {
+            exclude: true,
+            ..Default::default()
+        });
+        let excluded = status();
+        assert_eq!(excluded.file, None);
+        assert_eq!(excluded.activity, None);
+
+        shared.settings.write().unwrap().privacy.files.clear();
+        *shared.privacy_active.write().unwrap() = Some("zoom".to_string());
+        let private = status();
+        assert_eq!(private.file, None);
+        assert!(private.activity.is_some());
+        assert_eq!(private.privacy.as_deref(), Some("zoom"));
+
+        shared.settings.write().unwrap().privacy.action = PrivacyAction::ClearPresence;
+        let cleared = status();
+        assert_eq!(cleared.file, None);
+        assert_eq!(cleared.activity, None);
+    }
+}
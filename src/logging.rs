@@ -1,10 +1,20 @@
-use std::fmt;
+use std::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 use chrono::Local;
 use owo_colors::OwoColorize;
 
 use crate::VERSION;
 
+static TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Sends log output to stderr, keeping stdout clean for command output.
+pub fn log_to_stderr() {
+    TO_STDERR.store(true, Ordering::Relaxed);
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum LogLevel {
@@ -42,7 +52,7 @@ fn print_colored(level: LogLevel, module: &str, message: &str) {
     let timestamp = Local::now().format("%H:%M:%S%.3f");
     let module_str = format!("[{}]", module).cyan().to_string();
 
-    println!(
+    let line = format!(
         "{} {}  {}  {} {}",
         timestamp.dimmed(),
         version_str.dimmed(),
@@ -50,6 +60,11 @@ fn print_colored(level: LogLevel, module: &str, message: &str) {
         module_str,
         message
     );
+    if TO_STDERR.load(Ordering::Relaxed) {
+        eprintln!("{line}");
+    } else {
+        println!("{line}");
+    }
 }
 
 pub fn log(level: LogLevel, module: &str, message: String) {
@@ -5,31 +5,60 @@
 
 use discord_rich_presence::{DiscordIpc, DiscordIpcClient, activity};
 use std::{
+    path::PathBuf,
     sync::{
         Arc, RwLock,
         atomic::{AtomicBool, Ordering},
     },
     thread,
-    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+    time::{Instant, SystemTime, UNIX_EPOCH},
 };
 use winit::event_loop::EventLoop;
 
+mod autostart;
+mod bundle;
+mod cli;
+
 mod figma;
-use figma::{FigmaState, scan_figma_active_tab};
+mod i18n;
+mod instance;
+use figma::{FigmaState, scan_figma_tabs};
+use i18n::fill;
+use instance::{Command, Shared};
 
 mod logging;
 
+mod notifications;
+use notifications::Notification;
+
+mod pause;
+
+mod policy;
+
+mod power;
+
+mod presence;
+use presence::PresenceContent;
+
+mod preview;
+
+mod privacy;
+use privacy::detect_sensitive_process;
+
+mod schedule;
+use schedule::ScheduleOutcome;
+
 mod settings;
 mod settings_window;
-use settings::Settings;
+use settings::{PrivacyAction, Settings};
 
 mod tray;
-use tray::TrayApp;
+use tray::{TrayApp, TrayEvent, notify};
 
 mod updater;
 use updater::core::{is_auto_update_enabled, set_auto_update_enabled};
 
-use crate::figma::{find_figma_pids, is_figma_focused};
+use crate::figma::{find_figma_pids, is_figma_focused, scan_processes};
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -48,8 +77,19 @@ fn attach_parent_console() {
     }
 }
 
-const FIGMA_POLLING_RATE_SECONDS: u64 = 5;
-const RP_UPDATE_RATE_SECONDS: u64 = 15;
+/// Removes `--config-dir <path>` or `--config-dir=<path>` from `args`.
+fn take_config_dir_arg(args: &mut Vec<String>) -> Option<PathBuf> {
+    let pos = args
+        .iter()
+        .position(|a| a == "--config-dir" || a.starts_with("--config-dir="))?;
+    let arg = args.remove(pos);
+    match arg.strip_prefix("--config-dir=") {
+        Some(path) => Some(PathBuf::from(path)),
+        None if pos < args.len() => Some(PathBuf::from(args.remove(pos))),
+        None => None,
+    }
+}
+
 fn main() {
     let sentry_dsn = option_env!("SENTRY_DSN");
     let _guard = sentry_dsn.map(|dsn| {
@@ -74,47 +114,152 @@ fn main() {
     #[cfg(target_os = "windows")]
     attach_parent_console();
 
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    settings::set_config_dir(take_config_dir_arg(&mut args));
+
+    if args.first().is_some_and(|a| a == "config") {
+        std::process::exit(cli::run_config(&args[1..]));
+    }
+
+    if args
+        .first()
+        .is_some_and(|a| cli::CONTROL_COMMANDS.contains(&a.as_str()))
+    {
+        std::process::exit(cli::run_control(&args));
+    }
+
     set_auto_update_enabled(!std::env::args().any(|a| a == "--no-update"));
 
     if std::env::args().any(|a| a == "--settings") {
+        // The tray's own fallback window passes --standalone, since it's
+        // the running instance asking.
+        if !std::env::args().any(|a| a == "--standalone")
+            && instance::request(Command::OpenSettings).is_ok()
+        {
+            return;
+        }
         settings_window::run();
         return;
     }
 
+    match instance::lock() {
+        Ok(true) => {}
+        Ok(false) => {
+            match instance::request(Command::ShowStatus) {
+                Ok(_) => {
+                    log_info!("main", "Already running, handed off to that instance");
+                }
+                Err(e) => {
+                    log_error!("main", "Already running, but couldn't reach it: {e:#}");
+                }
+            }
+            return;
+        }
+        Err(e) => {
+            log_warn!("main", "Running without the single-instance lock: {e:#}");
+        }
+    }
+
+    // Updates that aren't installed at startup are announced instead.
+    let mut announce_update = !is_auto_update_enabled();
     if is_auto_update_enabled() {
         updater::install::cleanup_old_binary();
-        let _ = updater::splash::run_startup_update_check();
+        announce_update = updater::splash::run_startup_update_check().is_err();
     }
 
     log_info!("main", "Starting figma-discord-rp v{}", VERSION);
     log_debug!("main", "Auto-update enabled: {}", is_auto_update_enabled());
+    log_debug!("main", "Config dir: {}", settings::config_dir().display());
 
     let running = Arc::new(AtomicBool::new(true));
     let figma_state = Arc::new(RwLock::new(FigmaState::default()));
     let figma_connected = Arc::new(AtomicBool::new(false));
     let discord_connected = Arc::new(AtomicBool::new(false));
     let settings = Arc::new(RwLock::new(Settings::load()));
+    autostart::detect(&mut settings.write().unwrap());
+    let privacy_active: Arc<RwLock<Option<String>>> = Arc::new(RwLock::new(None));
+    let pause_state = Arc::new(RwLock::new(pause::load()));
+
+    if announce_update {
+        thread::spawn({
+            let settings = Arc::clone(&settings);
+            move || {
+                if let Ok(Some(release)) = updater::core::check_for_updates(&ureq::Agent::new()) {
+                    let settings = settings.read().unwrap();
+                    let body = fill(settings.strings().notify_update_body, &[&release.version]);
+                    notifications::show(&settings, Notification::UpdateAvailable, &body);
+                }
+            }
+        });
+    }
+
+    // Created up front so the worker threads can wake the tray when
+    // something it shows changes.
+    let event_loop = EventLoop::<TrayEvent>::with_user_event().build().unwrap();
+    let proxy = event_loop.create_proxy();
+
+    if let Err(e) = instance::listen({
+        let proxy = proxy.clone();
+        move |command, reply| {
+            let _ = proxy.send_event(TrayEvent::Control(command, reply));
+        }
+    }) {
+        log_warn!("main", "Later launches can't hand off: {e:#}");
+    }
+
+    thread::spawn({
+        let settings = Arc::clone(&settings);
+        let running = Arc::clone(&running);
+        let proxy = proxy.clone();
+        move || settings::watch(&settings, &running, || notify(&proxy))
+    });
 
     thread::spawn({
         let figma_state = Arc::clone(&figma_state);
         let figma_connected = Arc::clone(&figma_connected);
+        let settings = Arc::clone(&settings);
+        let privacy_active = Arc::clone(&privacy_active);
         let running = Arc::clone(&running);
+        let proxy = proxy.clone();
         move || {
             while running.load(Ordering::Relaxed) {
-                let pids = find_figma_pids();
+                let sys = scan_processes();
+
+                let privacy = settings.read().unwrap().privacy.clone();
+                let detected = if privacy.enabled {
+                    detect_sensitive_process(&sys, &privacy.processes)
+                } else {
+                    None
+                };
+                {
+                    let mut active = privacy_active.write().unwrap();
+                    if *active != detected {
+                        if let Some(name) = &detected {
+                            log_info!("privacy", "Enabled, detected \"{name}\"");
+                        } else {
+                            log_info!("privacy", "Disabled");
+                        }
+                        *active = detected;
+                        notify(&proxy);
+                    }
+                }
+
+                let pids = find_figma_pids(&sys);
                 if pids.is_empty() {
                     if figma_connected.swap(false, Ordering::Relaxed) {
                         log_warn!("figma", "Process not found, disconnecting");
                         let mut state = figma_state.write().unwrap();
                         *state = FigmaState::default();
+                        notify(&proxy);
                     }
-                    thread::sleep(Duration::from_secs(FIGMA_POLLING_RATE_SECONDS));
+                    thread::sleep(settings.read().unwrap().advanced.intervals().figma_polling);
                     continue;
                 }
 
-                match scan_figma_active_tab() {
-                    Ok(new_tab) => {
-                        if !figma_connected.swap(true, Ordering::Relaxed) {
+                match scan_figma_tabs() {
+                    Ok(tabs) => {
+                        let connected = !figma_connected.swap(true, Ordering::Relaxed);
+                        if connected {
                             log_info!("figma", "Connected");
                             let pid_list: Vec<String> =
                                 pids.iter().map(|p| p.to_string()).collect();
@@ -125,8 +270,15 @@ fn main() {
                                 pid_list.join(", ")
                             );
                         }
+                        let focused = is_figma_focused(&pids);
+                        let idle_threshold =
+                            settings.read().unwrap().advanced.intervals().idle_threshold;
                         let mut state = figma_state.write().unwrap();
+                        // Coming back from idle is news to the tray; going
+                        // idle it works out from the focus time itself.
+                        let mut changed = connected || (focused && state.is_idle(idle_threshold));
 
+                        let new_tab = tabs.first().cloned();
                         if new_tab != state.active_tab {
                             let title = new_tab
                                 .as_ref()
@@ -139,21 +291,39 @@ fn main() {
                                 .unwrap_or("none");
                             log_debug!("figma", "Tab changed: \"{}\" ({})", title, editor);
                             state.active_tab = new_tab;
+                            changed = true;
                         }
-                        if is_figma_focused(&pids) {
+                        changed |= state.open_tabs != tabs;
+                        state.open_tabs = tabs;
+                        if focused {
                             state.last_focused_at = Some(Instant::now());
                         }
+                        if changed {
+                            notify(&proxy);
+                        }
                     }
                     Err(e) => {
                         sentry::integrations::anyhow::capture_anyhow(&e);
-                        if figma_connected.swap(false, Ordering::Relaxed) {
-                            log_warn!("figma", "Disconnected: {e}");
+                        let disconnected = figma_connected.swap(false, Ordering::Relaxed);
+                        // Scans keep failing for as long as Figma hasn't
+                        // written its settings, so only losing the
+                        // connection or a file that can't be parsed is news.
+                        if disconnected || e.downcast_ref::<serde_json::Error>().is_some() {
+                            notifications::show(
+                                &settings.read().unwrap(),
+                                Notification::FigmaError,
+                                &format!("{e:#}"),
+                            );
+                        }
+                        if disconnected {
+                            log_warn!("figma", "Disconnected: {e:#}");
                             let mut state = figma_state.write().unwrap();
                             *state = FigmaState::default();
+                            notify(&proxy);
                         }
                     }
                 }
-                thread::sleep(Duration::from_secs(FIGMA_POLLING_RATE_SECONDS));
+                thread::sleep(settings.read().unwrap().advanced.intervals().figma_polling);
             }
         }
     });
@@ -163,9 +333,20 @@ fn main() {
         let figma_connected = Arc::clone(&figma_connected);
         let discord_connected = Arc::clone(&discord_connected);
         let settings = Arc::clone(&settings);
+        let privacy_active = Arc::clone(&privacy_active);
+        let pause_state = Arc::clone(&pause_state);
         let running = Arc::clone(&running);
+        let proxy = proxy.clone();
         move || {
             let mut client = DiscordIpcClient::new(env!("DISCORD_APP_ID"));
+            let update_interval = || {
+                settings
+                    .read()
+                    .unwrap()
+                    .advanced
+                    .intervals()
+                    .presence_update
+            };
 
             loop {
                 if !running.load(Ordering::Relaxed) {
@@ -174,33 +355,65 @@ fn main() {
                 match client.connect() {
                     Ok(_) => {
                         discord_connected.store(true, Ordering::Relaxed);
+                        notify(&proxy);
                         log_info!("discord", "Connected");
                         log_debug!("discord", "Self pid: {}", std::process::id());
                         break;
                     }
                     Err(e) => {
+                        let retry_in = update_interval();
                         log_error!(
                             "discord",
-                            "Connect failed: {e}, retrying in {RP_UPDATE_RATE_SECONDS}s"
+                            "Connect failed: {e}, retrying in {}s",
+                            retry_in.as_secs()
                         );
-                        thread::sleep(Duration::from_secs(RP_UPDATE_RATE_SECONDS));
+                        thread::sleep(retry_in);
                     }
                 }
             }
 
             let mut session_start: Option<i64> = None;
             let mut was_figma_connected = false;
+            let mut last_schedule = ScheduleOutcome::Normal;
 
             while running.load(Ordering::Relaxed) {
+                let schedule = schedule::evaluate(
+                    &settings.read().unwrap().schedule,
+                    chrono::Local::now().naive_local(),
+                );
+                if schedule != last_schedule {
+                    log_info!("schedule", "Outcome changed to {schedule:?}");
+                    last_schedule = schedule;
+                }
+
+                if pause_state.read().unwrap().is_paused() || schedule == ScheduleOutcome::Disabled
+                {
+                    let _ = client.clear_activity();
+                    was_figma_connected = false;
+                    session_start = None;
+                    thread::sleep(update_interval());
+                    continue;
+                }
+
                 let figma_up = figma_connected.load(Ordering::Relaxed);
 
-                let has_active_tab = figma_up && figma_state.read().unwrap().active_tab.is_some();
+                // Settings before Figma state, in the same order as every
+                // other reader, so a waiting writer can't deadlock them.
+                let has_active_tab = figma_up && {
+                    let settings = settings.read().unwrap();
+                    figma_state
+                        .read()
+                        .unwrap()
+                        .active_tab
+                        .as_ref()
+                        .is_some_and(|tab| !presence::is_excluded(&settings, tab))
+                };
 
                 if !figma_up || !has_active_tab {
                     let _ = client.clear_activity();
                     was_figma_connected = false;
                     session_start = None;
-                    thread::sleep(Duration::from_secs(RP_UPDATE_RATE_SECONDS));
+                    thread::sleep(update_interval());
                     continue;
                 }
 
@@ -214,35 +427,28 @@ fn main() {
                     was_figma_connected = true;
                 }
 
-                let (title, status, state_key) = {
-                    let figma = figma_state.read().unwrap();
-                    let disable_idle = settings.read().unwrap().disable_idle;
-                    let title = figma
-                        .active_tab
-                        .as_ref()
-                        .and_then(|t| t.title.clone())
-                        .unwrap_or_else(|| "Unknown".to_string());
-                    let (status, state_key) = if figma.is_idle() && !disable_idle {
-                        ("Idle".to_string(), "idle".to_string())
-                    } else {
-                        (figma.status(), figma.state_key().to_string())
-                    };
-                    (title, status, state_key)
-                };
+                let privacy_on = privacy_active.read().unwrap().is_some()
+                    || schedule == ScheduleOutcome::Privacy;
+                if privacy_on
+                    && settings.read().unwrap().privacy.action == PrivacyAction::ClearPresence
+                {
+                    let _ = client.clear_activity();
+                    thread::sleep(update_interval());
+                    continue;
+                }
 
-                let (image_url, app_name, hide_filename) = {
-                    let s = settings.read().unwrap();
-                    (
-                        s.image_url_for_state(&state_key).to_string(),
-                        s.resolved_app_name().to_string(),
-                        s.hide_filename,
-                    )
-                };
+                let PresenceContent {
+                    app_name,
+                    details,
+                    status,
+                    image: image_url,
+                    ..
+                } = PresenceContent::build(
+                    &settings.read().unwrap(),
+                    &figma_state.read().unwrap(),
+                    privacy_on,
+                );
 
-                let details = match hide_filename {
-                    true => None,
-                    false => Some(format!("File: {title}")),
-                };
                 log_debug!(
                     "discord",
                     "Setting activity: status={status}, app={app_name}, image={image_url}"
@@ -264,7 +470,13 @@ fn main() {
                 if let Err(e) = client.set_activity(activity) {
                     sentry::capture_error(&e);
                     discord_connected.store(false, Ordering::Relaxed);
+                    notify(&proxy);
                     log_error!("discord", "Failed to set activity: {e}, reconnecting");
+                    {
+                        let settings = settings.read().unwrap();
+                        let body = settings.strings().notify_discord_lost_body;
+                        notifications::show(&settings, Notification::DiscordLost, body);
+                    }
 
                     loop {
                         if !running.load(Ordering::Relaxed) {
@@ -273,32 +485,40 @@ fn main() {
                         match client.reconnect() {
                             Ok(_) => {
                                 discord_connected.store(true, Ordering::Relaxed);
+                                notify(&proxy);
                                 log_info!("discord", "Reconnected");
+                                let settings = settings.read().unwrap();
+                                let body = settings.strings().notify_discord_back_body;
+                                notifications::show(&settings, Notification::DiscordBack, body);
                                 break;
                             }
                             Err(e) => {
+                                let retry_in = update_interval();
                                 log_error!(
                                     "discord",
-                                    "Reconnect failed: {e}, retrying in {RP_UPDATE_RATE_SECONDS}s"
+                                    "Reconnect failed: {e}, retrying in {}s",
+                                    retry_in.as_secs()
                                 );
-                                thread::sleep(Duration::from_secs(RP_UPDATE_RATE_SECONDS));
+                                thread::sleep(retry_in);
                             }
                         }
                     }
                 }
 
-                thread::sleep(Duration::from_secs(RP_UPDATE_RATE_SECONDS));
+                thread::sleep(update_interval());
             }
         }
     });
 
-    let event_loop = EventLoop::new().unwrap();
-    let mut app = TrayApp::new(
+    let shared = Shared {
         running,
+        settings,
         figma_state,
         figma_connected,
         discord_connected,
-        settings,
-    );
+        privacy_active,
+        pause_state,
+    };
+    let mut app = TrayApp::new(shared, proxy);
     event_loop.run_app(&mut app).unwrap();
 }
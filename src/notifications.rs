@@ -0,0 +1,87 @@
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    i18n::Strings,
+    log_debug, log_warn,
+    settings::{NotificationSettings, Settings},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Notification {
+    DiscordLost,
+    DiscordBack,
+    FigmaError,
+    SettingsProblem,
+    UpdateAvailable,
+}
+
+impl Notification {
+    fn enabled(self, settings: &NotificationSettings) -> bool {
+        settings.enabled
+            && match self {
+                Self::DiscordLost | Self::DiscordBack => settings.discord,
+                Self::FigmaError => settings.figma,
+                Self::SettingsProblem => settings.settings,
+                Self::UpdateAvailable => settings.updates,
+            }
+    }
+
+    fn title(self, strings: &Strings) -> &'static str {
+        match self {
+            Self::DiscordLost => strings.notify_discord_lost,
+            Self::DiscordBack => strings.notify_discord_back,
+            Self::FigmaError => strings.notify_figma_error,
+            Self::SettingsProblem => strings.notify_settings_problem,
+            Self::UpdateAvailable => strings.notify_update,
+        }
+    }
+}
+
+/// When each kind of notification was last shown.
+static LAST_SHOWN: LazyLock<Mutex<HashMap<Notification, Instant>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Shows a desktop notification unless its category is turned off or the
+/// same kind was shown within the configured interval.
+pub fn show(settings: &Settings, notification: Notification, body: &str) {
+    let config = &settings.notifications;
+    if !notification.enabled(config) {
+        return;
+    }
+    {
+        let mut last_shown = LAST_SHOWN.lock().unwrap();
+        let interval = Duration::from_secs(config.min_interval_secs);
+        if last_shown
+            .get(&notification)
+            .is_some_and(|at| at.elapsed() < interval)
+        {
+            log_debug!("notify", "Skipped {notification:?}, shown recently");
+            return;
+        }
+        last_shown.insert(notification, Instant::now());
+    }
+
+    show_now(notification.title(settings.strings()), body);
+}
+
+/// Shows a desktop notification right away, for when the user asked for it.
+pub fn show_now(title: &str, body: &str) {
+    let (title, body) = (title.to_string(), body.to_string());
+    // Delivery can block on the notification service, so keep it off the
+    // caller's thread.
+    thread::spawn(move || {
+        if let Err(e) = notify_rust::Notification::new()
+            .appname("Figma Rich Presence")
+            .summary(&title)
+            .body(&body)
+            .show()
+        {
+            log_warn!("notify", "Failed to show notification: {e}");
+        }
+    });
+}
@@ -0,0 +1,156 @@
+use chrono::{DateTime, Datelike, Days, Duration, Local, NaiveTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+
+use crate::{
+    i18n::{Strings, fill},
+    log_debug, log_warn,
+    settings::{config_dir, write_atomic},
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PauseState {
+    #[default]
+    Active,
+    Until(DateTime<Local>),
+    Indefinite,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PauseDuration {
+    Minutes15,
+    Hour1,
+    UntilTomorrow,
+    UntilResumed,
+}
+
+impl PauseDuration {
+    pub const ALL: [PauseDuration; 4] = [
+        Self::Minutes15,
+        Self::Hour1,
+        Self::UntilTomorrow,
+        Self::UntilResumed,
+    ];
+
+    pub fn label(&self, strings: &Strings) -> &'static str {
+        match self {
+            Self::Minutes15 => strings.pause_15_minutes,
+            Self::Hour1 => strings.pause_1_hour,
+            Self::UntilTomorrow => strings.pause_until_tomorrow,
+            Self::UntilResumed => strings.pause_until_resumed,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PersistedPause {
+    until: i64,
+}
+
+fn next_midnight(now: DateTime<Local>) -> DateTime<Local> {
+    let tomorrow = now
+        .date_naive()
+        .checked_add_days(Days::new(1))
+        .unwrap_or(now.date_naive())
+        .and_time(NaiveTime::MIN);
+    Local
+        .from_local_datetime(&tomorrow)
+        .earliest()
+        .unwrap_or(now + Duration::days(1))
+}
+
+impl PauseState {
+    pub fn start(duration: PauseDuration) -> Self {
+        let now = Local::now();
+        let state = match duration {
+            PauseDuration::Minutes15 => Self::Until(now + Duration::minutes(15)),
+            PauseDuration::Hour1 => Self::Until(now + Duration::hours(1)),
+            PauseDuration::UntilTomorrow => Self::Until(next_midnight(now)),
+            PauseDuration::UntilResumed => Self::Indefinite,
+        };
+        if duration == PauseDuration::UntilTomorrow {
+            persist(state);
+        } else {
+            persist(Self::Active);
+        }
+        state
+    }
+
+    /// A pause of any length, as asked for by the `pause` command. Like the
+    /// short menu choices, it doesn't survive a restart.
+    pub fn for_minutes(minutes: u32) -> Self {
+        persist(Self::Active);
+        Self::Until(Local::now() + Duration::minutes(minutes.into()))
+    }
+
+    pub fn is_paused(&self) -> bool {
+        match self {
+            Self::Active => false,
+            Self::Until(until) => Local::now() < *until,
+            Self::Indefinite => true,
+        }
+    }
+
+    pub fn has_expired(&self) -> bool {
+        matches!(self, Self::Until(_)) && !self.is_paused()
+    }
+
+    pub fn describe(&self, strings: &Strings) -> Option<String> {
+        match self {
+            Self::Active => None,
+            Self::Until(_) if !self.is_paused() => None,
+            Self::Until(until) if until.date_naive() == Local::now().date_naive() => {
+                Some(fill(strings.paused_until, &[&until.format("%H:%M")]))
+            }
+            Self::Until(until) => {
+                let day = strings.weekday(until.weekday());
+                let time = until.format("%H:%M");
+                Some(fill(strings.paused_until, &[&format!("{day} {time}")]))
+            }
+            Self::Indefinite => Some(strings.paused.to_string()),
+        }
+    }
+}
+
+fn path() -> PathBuf {
+    config_dir().join("pause.json")
+}
+
+/// Only pauses lasting until tomorrow survive a restart; shorter ones are
+/// assumed stale by the time the app comes back.
+pub fn persist(state: PauseState) {
+    let path = path();
+    match state {
+        PauseState::Until(until) => {
+            let data = PersistedPause {
+                until: until.timestamp(),
+            };
+            if let Ok(data) = serde_json::to_string(&data)
+                && let Err(e) = write_atomic(&path, data.as_bytes())
+            {
+                log_warn!("pause", "Failed to persist pause: {e}");
+            }
+        }
+        PauseState::Active | PauseState::Indefinite => {
+            let _ = fs::remove_file(&path);
+        }
+    }
+}
+
+pub fn load() -> PauseState {
+    let Ok(data) = fs::read_to_string(path()) else {
+        return PauseState::Active;
+    };
+    let state = serde_json::from_str::<PersistedPause>(&data)
+        .ok()
+        .and_then(|p| Local.timestamp_opt(p.until, 0).single())
+        .map(PauseState::Until)
+        .filter(PauseState::is_paused)
+        .unwrap_or_default();
+    if state == PauseState::Active {
+        persist(state);
+    } else {
+        log_debug!("pause", "Restored pause: {:?}", state);
+    }
+    state
+}
@@ -0,0 +1,206 @@
+use serde_json::Value;
+use std::{fs, path::PathBuf, sync::OnceLock};
+
+use crate::{log_info, log_warn};
+
+#[cfg_attr(test, allow(dead_code))]
+static POLICY: OnceLock<Option<Value>> = OnceLock::new();
+
+/// System-wide file, writable by administrators only, whose values override
+/// the user's settings and can't be changed from the app.
+pub fn path() -> PathBuf {
+    #[cfg(target_os = "windows")]
+    {
+        let program_data =
+            std::env::var_os("ProgramData").unwrap_or_else(|| "C:\\ProgramData".into());
+        PathBuf::from(program_data)
+            .join("figma-discord-rp")
+            .join("policy.json")
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        PathBuf::from("/Library/Application Support/figma-discord-rp/policy.json")
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        PathBuf::from("/etc/figma-discord-rp/policy.json")
+    }
+}
+
+#[cfg_attr(test, allow(dead_code))]
+fn load() -> Option<Value> {
+    let path = path();
+    let data = fs::read_to_string(&path).ok()?;
+    match serde_json::from_str::<Value>(&data) {
+        Ok(policy @ Value::Object(_)) => {
+            log_info!("policy", "Loaded managed settings from {}", path.display());
+            Some(policy)
+        }
+        Ok(_) => {
+            log_warn!("policy", "Ignoring {}: not a JSON object", path.display());
+            None
+        }
+        Err(e) => {
+            log_warn!("policy", "Ignoring {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+pub fn get() -> Option<&'static Value> {
+    #[cfg(test)]
+    return *TEST_POLICY.lock().unwrap_or_else(|e| e.into_inner());
+    #[cfg(not(test))]
+    POLICY.get_or_init(load).as_ref()
+}
+
+/// Stands in for the policy file in tests, which never read the host's.
+#[cfg(test)]
+static TEST_POLICY: std::sync::Mutex<Option<&'static Value>> = std::sync::Mutex::new(None);
+
+/// Sets the policy [`get`] returns until the next call. Leaks `policy`,
+/// which is fine for the handful of tests that set one.
+#[cfg(test)]
+pub fn set_for_test(policy: Option<Value>) {
+    *TEST_POLICY.lock().unwrap_or_else(|e| e.into_inner()) =
+        policy.map(|policy| &*Box::leak(Box::new(policy)));
+}
+
+/// Whether `key` (dotted, as used by `config get`) or any of its parents is
+/// set by the policy.
+pub fn is_locked(key: &str) -> bool {
+    let Some(mut value) = get() else {
+        return false;
+    };
+    for part in key.split('.') {
+        match value.get(part) {
+            Some(inner @ Value::Object(map)) if !map.is_empty() => value = inner,
+            Some(_) => return true,
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Undoes [`merge`] for the keys `patch` sets: each gets its value from
+/// `original` back, or is removed when `original` didn't have it.
+pub fn unmerge(target: &mut Value, patch: &Value, original: Option<&Value>) {
+    let (Value::Object(target), Value::Object(patch)) = (target, patch) else {
+        return;
+    };
+    for (key, managed) in patch {
+        let original = original.and_then(|o| o.get(key));
+        let Some(inner) = target.get_mut(key) else {
+            continue;
+        };
+        if managed.as_object().is_some_and(|m| !m.is_empty()) && inner.is_object() {
+            unmerge(inner, managed, original);
+        } else if let Some(value) = original {
+            *inner = value.clone();
+        } else {
+            target.remove(key);
+        }
+    }
+}
+
+/// Recursively overwrites `target` with the values in `patch`.
+pub fn merge(target: &mut Value, patch: &Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                merge(target.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+        (target, patch) => *target = patch.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::{Settings, test_env::TestEnv};
+    use serde_json::json;
+
+    #[test]
+    fn merge_overwrites_nested_values() {
+        let mut doc = json!({"a": 1, "nested": {"keep": true, "set": 1}});
+        merge(
+            &mut doc,
+            &json!({"nested": {"set": 2, "new": "x"}, "b": [1]}),
+        );
+        assert_eq!(
+            doc,
+            json!({"a": 1, "b": [1], "nested": {"keep": true, "set": 2, "new": "x"}})
+        );
+    }
+
+    #[test]
+    fn unmerge_restores_the_original() {
+        let original = json!({"a": 1, "nested": {"keep": true, "set": 1}});
+        let policy = json!({"a": 5, "b": [1], "nested": {"set": 2, "new": "x"}});
+        let mut doc = original.clone();
+        merge(&mut doc, &policy);
+        unmerge(&mut doc, &policy, Some(&original));
+        assert_eq!(doc, original);
+
+        let mut doc = original.clone();
+        merge(&mut doc, &policy);
+        unmerge(&mut doc, &policy, None);
+        assert_eq!(doc, json!({"nested": {"keep": true}}));
+    }
+
+    #[test]
+    fn is_locked_covers_policy_keys_and_their_children() {
+        let env = TestEnv::with_policy(json!({
+            "hide_filename": true,
+            "advanced": {"battery_saver": false},
+            "image_overrides": {},
+        }));
+        assert!(is_locked("hide_filename"));
+        assert!(is_locked("advanced"));
+        assert!(is_locked("advanced.battery_saver"));
+        assert!(!is_locked("advanced.idle_threshold_secs"));
+        assert!(is_locked("image_overrides.design.enabled"));
+        assert!(!is_locked("disable_idle"));
+
+        drop(env);
+        let _env = TestEnv::new();
+        assert!(!is_locked("hide_filename"));
+    }
+
+    #[test]
+    fn managed_values_stay_out_of_the_users_file() {
+        let env = TestEnv::with_policy(json!({
+            "hide_filename": true,
+            "advanced": {"battery_saver": false},
+        }));
+        let path = env.path().join("settings.json");
+        let user = json!({
+            "version": 2,
+            "hide_filename": false,
+            "advanced": {"idle_threshold_secs": 600},
+            "active_profile": "Default",
+            "profiles": [{"name": "Default", "hide_filename": false}],
+        });
+        fs::write(&path, user.to_string()).unwrap();
+
+        let settings = Settings::load();
+        assert!(settings.hide_filename);
+        assert!(!settings.advanced.battery_saver);
+        Settings {
+            disable_idle: true,
+            ..settings
+        }
+        .save()
+        .unwrap();
+
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["hide_filename"], json!(false));
+        assert_eq!(saved["disable_idle"], json!(true));
+        assert_eq!(saved["advanced"]["idle_threshold_secs"], json!(600));
+        assert_eq!(saved["advanced"].get("battery_saver"), None);
+        assert_eq!(saved["profiles"][0]["hide_filename"], json!(false));
+    }
+}
@@ -0,0 +1,77 @@
+use std::{
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+static CACHE: Mutex<Cache> = Mutex::new(Cache {
+    on_battery: false,
+    checked_at: None,
+    checking: false,
+});
+
+struct Cache {
+    on_battery: bool,
+    checked_at: Option<Instant>,
+    /// A query is running on a background thread.
+    checking: bool,
+}
+
+#[cfg(target_os = "windows")]
+fn query_on_battery() -> bool {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return false;
+    }
+    status.ACLineStatus == 0
+}
+
+#[cfg(target_os = "macos")]
+fn query_on_battery() -> bool {
+    std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .map(|out| String::from_utf8_lossy(&out.stdout).contains("'Battery Power'"))
+        .unwrap_or(false)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn query_on_battery() -> bool {
+    let Ok(entries) = std::fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+    let read = |path: &std::path::Path, name: &str| {
+        std::fs::read_to_string(path.join(name))
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default()
+    };
+    entries.flatten().any(|entry| {
+        let path = entry.path();
+        read(&path, "type") == "Battery" && read(&path, "status") == "Discharging"
+    })
+}
+
+/// Whether the machine is running on battery, as of the last check. Checks
+/// run on a background thread at most once a minute, since this is asked
+/// on every poll (often from the UI thread) and may spawn a process on
+/// macOS. Until the first check finishes, the machine counts as plugged in.
+pub fn on_battery() -> bool {
+    let mut cache = CACHE.lock().unwrap();
+    let stale = cache
+        .checked_at
+        .is_none_or(|checked_at| checked_at.elapsed() >= CHECK_INTERVAL);
+    if stale && !cache.checking {
+        cache.checking = true;
+        thread::spawn(|| {
+            let on_battery = query_on_battery();
+            let mut cache = CACHE.lock().unwrap();
+            cache.on_battery = on_battery;
+            cache.checked_at = Some(Instant::now());
+            cache.checking = false;
+        });
+    }
+    cache.on_battery
+}
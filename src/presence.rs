@@ -0,0 +1,64 @@
+use crate::{
+    figma::{FigmaState, FigmaTab},
+    i18n::fill,
+    settings::{FileRule, Settings},
+};
+
+fn rule_for<'a>(settings: &'a Settings, tab: &FigmaTab) -> Option<&'a FileRule> {
+    settings.file_rule(&tab.file_id()?)
+}
+
+/// Whether the file was excluded from tracking, so nothing is shown for it.
+pub fn is_excluded(settings: &Settings, tab: &FigmaTab) -> bool {
+    rule_for(settings, tab).is_some_and(|rule| rule.exclude)
+}
+
+/// What the Discord activity shows, independent of the IPC client.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PresenceContent {
+    pub app_name: String,
+    /// The file name as shown, `None` when it's hidden.
+    pub title: Option<String>,
+    pub state_key: &'static str,
+    pub details: Option<String>,
+    pub status: String,
+    pub image: String,
+}
+
+impl PresenceContent {
+    pub fn build(settings: &Settings, figma: &FigmaState, privacy_on: bool) -> Self {
+        let strings = settings.strings();
+        let idle_threshold = settings.advanced.intervals().idle_threshold;
+        let rule = figma
+            .active_tab
+            .as_ref()
+            .and_then(|tab| rule_for(settings, tab));
+        let title = rule
+            .map(|rule| rule.alias.trim())
+            .filter(|alias| !alias.is_empty())
+            .or_else(|| figma.active_tab.as_ref().and_then(|t| t.title.as_deref()))
+            .unwrap_or(strings.unknown_file);
+        let hide_name = rule.is_some_and(|rule| rule.hide_name);
+        let state_key = if figma.is_idle(idle_threshold) && !settings.disable_idle {
+            "idle"
+        } else {
+            figma.state_key()
+        };
+        let title = match settings.hide_filename || privacy_on || hide_name {
+            true => None,
+            false => Some(title.to_string()),
+        };
+        let details = title
+            .as_ref()
+            .map(|title| fill(strings.file_details, &[title]));
+
+        Self {
+            app_name: settings.resolved_app_name().to_string(),
+            title,
+            state_key,
+            details,
+            status: strings.state(state_key).to_string(),
+            image: settings.image_url_for_state(state_key).to_string(),
+        }
+    }
+}
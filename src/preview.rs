@@ -0,0 +1,454 @@
+use chrono::Local;
+use eframe::egui;
+use std::{
+    collections::HashMap,
+    io::Read,
+    sync::{
+        Arc, RwLock,
+        mpsc::{self, Receiver, Sender},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    figma::{FigmaState, FigmaTab, scan_figma_tabs, scan_processes},
+    i18n::{Strings, fill},
+    presence::{self, PresenceContent},
+    privacy::detect_sensitive_process,
+    schedule::{self, ScheduleOutcome},
+    settings::{FALLBACK_IMAGE, ImageKind, PrivacyAction, Settings, check_image},
+};
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_IMAGE_BYTES: u64 = 4 * 1024 * 1024;
+const SCAN_INTERVAL: Duration = Duration::from_secs(5);
+const FETCH_DELAY: Duration = Duration::from_millis(600);
+const RETRY_AFTER: Duration = Duration::from_secs(30);
+const MAX_CACHED_IMAGES: usize = 16;
+const IMAGE_SIZE: f32 = 80.0;
+const BADGE_SIZE: f32 = 28.0;
+const CARD_COLOR: egui::Color32 = egui::Color32::from_rgb(0x23, 0x24, 0x28);
+
+enum ImageState {
+    /// Seen, but not fetched until it stops changing.
+    Pending(Instant),
+    Loading,
+    Loaded(egui::TextureHandle),
+    Failed(Instant),
+}
+
+struct CachedImage {
+    state: ImageState,
+    used: Instant,
+}
+
+/// Textures for image URLs, fetched on background threads so the window
+/// never blocks on the network. A URL is only fetched once it has stayed
+/// the same for [`FETCH_DELAY`], so typing one doesn't fetch every prefix.
+struct ImageCache {
+    images: HashMap<String, CachedImage>,
+    tx: Sender<(String, Option<egui::ColorImage>)>,
+    rx: Receiver<(String, Option<egui::ColorImage>)>,
+}
+
+fn fetch_image(url: &str) -> Option<egui::ColorImage> {
+    let agent = ureq::AgentBuilder::new().timeout(FETCH_TIMEOUT).build();
+    let response = agent
+        .get(url)
+        .set("User-Agent", crate::updater::user_agent())
+        .call()
+        .ok()?;
+    let mut bytes = Vec::new();
+    response
+        .into_reader()
+        .take(MAX_IMAGE_BYTES)
+        .read_to_end(&mut bytes)
+        .ok()?;
+    let image = image::load_from_memory(&bytes).ok()?.into_rgba8();
+    let size = [image.width() as usize, image.height() as usize];
+    Some(egui::ColorImage::from_rgba_unmultiplied(
+        size,
+        image.as_raw(),
+    ))
+}
+
+/// Asks the server whether `url` serves an image, without downloading it.
+pub fn probe_image(url: &str) -> Result<(), String> {
+    let agent = ureq::AgentBuilder::new().timeout(FETCH_TIMEOUT).build();
+    let response = agent
+        .head(url)
+        .set("User-Agent", crate::updater::user_agent())
+        .call()
+        .map_err(|e| match e {
+            ureq::Error::Status(code, _) => format!("server answered {code}"),
+            ureq::Error::Transport(e) => format!("couldn't reach server: {}", e.kind()),
+        })?;
+    match response.header("Content-Type") {
+        Some(content_type) if !content_type.starts_with("image/") => {
+            Err(format!("not an image ({content_type})"))
+        }
+        _ => Ok(()),
+    }
+}
+
+pub enum ProbeState {
+    Checking,
+    Ok,
+    Failed(String),
+}
+
+/// Results of [`probe_image`] for URLs checked from the settings window.
+pub struct ImageProbes {
+    results: HashMap<String, ProbeState>,
+    tx: Sender<(String, Result<(), String>)>,
+    rx: Receiver<(String, Result<(), String>)>,
+}
+
+impl ImageProbes {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            results: HashMap::new(),
+            tx,
+            rx,
+        }
+    }
+
+    pub fn start(&mut self, ctx: &egui::Context, url: &str) {
+        if matches!(self.results.get(url), Some(ProbeState::Checking)) {
+            return;
+        }
+        self.results.insert(url.to_string(), ProbeState::Checking);
+        let tx = self.tx.clone();
+        let ctx = ctx.clone();
+        let url = url.to_string();
+        thread::spawn(move || {
+            let result = probe_image(&url);
+            let _ = tx.send((url, result));
+            ctx.request_repaint();
+        });
+    }
+
+    pub fn get(&mut self, url: &str) -> Option<&ProbeState> {
+        while let Ok((url, result)) = self.rx.try_recv() {
+            let state = match result {
+                Ok(()) => ProbeState::Ok,
+                Err(e) => ProbeState::Failed(e),
+            };
+            self.results.insert(url, state);
+        }
+        self.results.get(url)
+    }
+}
+
+impl ImageCache {
+    fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            images: HashMap::new(),
+            tx,
+            rx,
+        }
+    }
+
+    fn get(&mut self, ctx: &egui::Context, url: &str) -> &ImageState {
+        let now = Instant::now();
+        while let Ok((url, image)) = self.rx.try_recv() {
+            let state = match image {
+                Some(image) => {
+                    ImageState::Loaded(ctx.load_texture(&url, image, Default::default()))
+                }
+                None => ImageState::Failed(now),
+            };
+            if let Some(cached) = self.images.get_mut(&url) {
+                cached.state = state;
+            }
+        }
+
+        if !self.images.contains_key(url) {
+            self.evict();
+        }
+        let cached = self.images.entry(url.to_string()).or_insert(CachedImage {
+            state: ImageState::Pending(now),
+            used: now,
+        });
+        cached.used = now;
+        let wait = match cached.state {
+            ImageState::Pending(since) => FETCH_DELAY.checked_sub(since.elapsed()),
+            ImageState::Failed(at) => RETRY_AFTER.checked_sub(at.elapsed()),
+            ImageState::Loading | ImageState::Loaded(_) => return &cached.state,
+        };
+        match wait {
+            Some(wait) => ctx.request_repaint_after(wait),
+            None => {
+                let tx = self.tx.clone();
+                let ctx = ctx.clone();
+                let url = url.to_string();
+                thread::spawn(move || {
+                    let image = fetch_image(&url);
+                    let _ = tx.send((url, image));
+                    ctx.request_repaint();
+                });
+                cached.state = ImageState::Loading;
+            }
+        }
+        &cached.state
+    }
+
+    /// Makes room for one more image by dropping the least recently shown.
+    fn evict(&mut self) {
+        while self.images.len() >= MAX_CACHED_IMAGES {
+            let Some(oldest) = self
+                .images
+                .iter()
+                .min_by_key(|(_, cached)| cached.used)
+                .map(|(url, _)| url.clone())
+            else {
+                break;
+            };
+            self.images.remove(&oldest);
+        }
+    }
+}
+
+enum Texture {
+    Ready(egui::TextureHandle),
+    Loading,
+    Missing,
+}
+
+/// An approximation of the Discord activity card for the settings being
+/// edited, fed by the Figma tab that is open right now.
+pub struct Preview {
+    images: ImageCache,
+    fallback: Option<egui::TextureHandle>,
+    /// The tracker's state when the window runs inside the main process;
+    /// otherwise kept up to date by a scan on a background thread.
+    figma: Arc<RwLock<FigmaState>>,
+    privacy_active: Arc<RwLock<Option<String>>>,
+    /// The process list being edited, for the background scan.
+    processes: Option<Arc<RwLock<Vec<String>>>>,
+    started: Instant,
+}
+
+/// Keeps `figma` and `privacy_active` current until the preview owning them
+/// is dropped, so the window never waits on Figma's files.
+fn spawn_scan(
+    figma: Arc<RwLock<FigmaState>>,
+    privacy_active: Arc<RwLock<Option<String>>>,
+    processes: Arc<RwLock<Vec<String>>>,
+) {
+    thread::spawn(move || {
+        while Arc::strong_count(&figma) > 1 {
+            let open_tabs = scan_figma_tabs().unwrap_or_default();
+            *figma.write().unwrap() = FigmaState {
+                active_tab: open_tabs.first().cloned(),
+                open_tabs,
+                last_focused_at: Some(Instant::now()),
+            };
+            let patterns = processes.read().unwrap().clone();
+            *privacy_active.write().unwrap() =
+                detect_sensitive_process(&scan_processes(), &patterns);
+            thread::sleep(SCAN_INTERVAL);
+        }
+    });
+}
+
+impl Preview {
+    pub fn new() -> Self {
+        let preview = Self {
+            images: ImageCache::new(),
+            fallback: None,
+            figma: Arc::default(),
+            privacy_active: Arc::default(),
+            processes: Some(Arc::default()),
+            started: Instant::now(),
+        };
+        spawn_scan(
+            Arc::clone(&preview.figma),
+            Arc::clone(&preview.privacy_active),
+            Arc::clone(preview.processes.as_ref().unwrap()),
+        );
+        preview
+    }
+
+    pub fn with_shared_state(
+        figma_state: Arc<RwLock<FigmaState>>,
+        privacy_active: Arc<RwLock<Option<String>>>,
+    ) -> Self {
+        Self {
+            images: ImageCache::new(),
+            fallback: None,
+            figma: figma_state,
+            privacy_active,
+            processes: None,
+            started: Instant::now(),
+        }
+    }
+
+    /// Whether Discord would be shown the privacy version of the activity,
+    /// judged by the settings being edited.
+    fn privacy_on(&self, settings: &Settings) -> bool {
+        if let Some(processes) = &self.processes
+            && *processes.read().unwrap() != settings.privacy.processes
+        {
+            *processes.write().unwrap() = settings.privacy.processes.clone();
+        }
+        let detected = settings.privacy.enabled && self.privacy_active.read().unwrap().is_some();
+        let scheduled = schedule::evaluate(&settings.schedule, Local::now().naive_local())
+            == ScheduleOutcome::Privacy;
+        detected || scheduled
+    }
+
+    fn fallback_texture(&mut self, ctx: &egui::Context) -> egui::TextureHandle {
+        self.fallback
+            .get_or_insert_with(|| {
+                let image = image::load_from_memory(include_bytes!("../assets/favicon.png"))
+                    .expect("failed to load preview icon")
+                    .into_rgba8();
+                let size = [image.width() as usize, image.height() as usize];
+                let image = egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw());
+                ctx.load_texture("preview-fallback", image, Default::default())
+            })
+            .clone()
+    }
+
+    fn texture(&mut self, ctx: &egui::Context, image: &str) -> Texture {
+        if image == FALLBACK_IMAGE {
+            Texture::Ready(self.fallback_texture(ctx))
+        } else if matches!(check_image(image), Ok(ImageKind::Url)) {
+            match self.images.get(ctx, image) {
+                ImageState::Loaded(texture) => Texture::Ready(texture.clone()),
+                ImageState::Pending(_) | ImageState::Loading => Texture::Loading,
+                ImageState::Failed(_) => Texture::Missing,
+            }
+        } else {
+            Texture::Missing
+        }
+    }
+
+    fn large_image(&mut self, ui: &mut egui::Ui, image: &str, strings: &Strings) -> egui::Rect {
+        let size = egui::vec2(IMAGE_SIZE, IMAGE_SIZE);
+        match self.texture(ui.ctx(), image) {
+            Texture::Ready(texture) => {
+                ui.add(
+                    egui::Image::from_texture(egui::load::SizedTexture::from_handle(&texture))
+                        .fit_to_exact_size(size)
+                        .rounding(8.0),
+                )
+                .rect
+            }
+            Texture::Loading => {
+                ui.allocate_ui(size, |ui| {
+                    ui.centered_and_justified(|ui| ui.spinner());
+                })
+                .response
+                .rect
+            }
+            Texture::Missing => {
+                let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+                ui.painter()
+                    .rect_filled(rect, 8.0, ui.visuals().extreme_bg_color);
+                ui.painter().text(
+                    rect.center(),
+                    egui::Align2::CENTER_CENTER,
+                    "?",
+                    egui::FontId::proportional(24.0),
+                    ui.visuals().weak_text_color(),
+                );
+                response.on_hover_text(fill(strings.preview_load_failed, &[&image]));
+                rect
+            }
+        }
+    }
+
+    /// Draws the small image as a round badge over the bottom right corner
+    /// of `large`, ringed in the card color like Discord does.
+    fn small_image(&mut self, ui: &mut egui::Ui, image: &str, large: egui::Rect, hover: &str) {
+        let Texture::Ready(texture) = self.texture(ui.ctx(), image) else {
+            return;
+        };
+        let center = large.right_bottom() - egui::vec2(BADGE_SIZE / 3.0, BADGE_SIZE / 3.0);
+        let rect = egui::Rect::from_center_size(center, egui::vec2(BADGE_SIZE, BADGE_SIZE));
+        ui.painter()
+            .circle_filled(center, BADGE_SIZE / 2.0 + 3.0, CARD_COLOR);
+        egui::Image::from_texture(egui::load::SizedTexture::from_handle(&texture))
+            .rounding(BADGE_SIZE / 2.0)
+            .paint_at(ui, rect);
+        ui.interact(rect, ui.id().with("preview-badge"), egui::Sense::hover())
+            .on_hover_text(hover);
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, settings: &Settings) {
+        let strings = settings.strings();
+        let privacy_on = self.privacy_on(settings);
+        let current = self.figma.read().unwrap().clone();
+        let example = current
+            .active_tab
+            .as_ref()
+            .is_none_or(|tab| presence::is_excluded(settings, tab));
+        let figma = if example {
+            FigmaState {
+                active_tab: Some(FigmaTab {
+                    title: Some(strings.untitled.to_string()),
+                    ..Default::default()
+                }),
+                last_focused_at: Some(Instant::now()),
+                ..Default::default()
+            }
+        } else {
+            current
+        };
+        let content = PresenceContent::build(settings, &figma, privacy_on);
+        let elapsed = self.started.elapsed().as_secs();
+
+        egui::Frame::none()
+            .fill(CARD_COLOR)
+            .rounding(8.0)
+            .inner_margin(12.0)
+            .show(ui, |ui| {
+                ui.set_width(ui.available_width());
+                ui.label(
+                    egui::RichText::new(strings.preview_playing)
+                        .small()
+                        .strong()
+                        .color(egui::Color32::from_gray(0xb5)),
+                );
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    let large = self.large_image(ui, &content.image, strings);
+                    // The card's small image slot, shown with the app icon
+                    // unless that's already the large image.
+                    if content.image != FALLBACK_IMAGE {
+                        self.small_image(ui, FALLBACK_IMAGE, large, &content.status);
+                    }
+                    ui.vertical(|ui| {
+                        ui.label(
+                            egui::RichText::new(&content.app_name)
+                                .strong()
+                                .color(egui::Color32::WHITE),
+                        );
+                        let text = egui::Color32::from_gray(0xdb);
+                        if let Some(details) = &content.details {
+                            ui.label(egui::RichText::new(details).color(text));
+                        }
+                        ui.label(egui::RichText::new(&content.status).color(text));
+                        ui.label(
+                            egui::RichText::new(fill(
+                                strings.preview_elapsed,
+                                &[&format!("{:02}:{:02}", elapsed / 60, elapsed % 60)],
+                            ))
+                            .color(text),
+                        );
+                    });
+                });
+            });
+        if privacy_on && settings.privacy.action == PrivacyAction::ClearPresence {
+            ui.weak(strings.preview_privacy_cleared);
+        } else if example {
+            ui.weak(strings.preview_example);
+        }
+        ui.ctx().request_repaint_after(Duration::from_secs(1));
+    }
+}
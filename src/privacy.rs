@@ -0,0 +1,29 @@
+use sysinfo::System;
+
+fn normalize(name: &str) -> String {
+    let name = name.trim().to_lowercase();
+    match name.strip_suffix(".exe") {
+        Some(stem) => stem.to_string(),
+        None => name,
+    }
+}
+
+/// Returns the name of the first running process matching one of `patterns`.
+///
+/// Names are compared case-insensitively and without a `.exe` suffix, but
+/// otherwise exactly, so that e.g. `obs` does not match `obsidian`.
+pub fn detect_sensitive_process(sys: &System, patterns: &[String]) -> Option<String> {
+    let patterns: Vec<String> = patterns
+        .iter()
+        .map(|p| normalize(p))
+        .filter(|p| !p.is_empty())
+        .collect();
+    if patterns.is_empty() {
+        return None;
+    }
+
+    sys.processes().values().find_map(|p| {
+        let name = normalize(&p.name().to_string_lossy());
+        patterns.contains(&name).then_some(name)
+    })
+}
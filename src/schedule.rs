@@ -0,0 +1,196 @@
+use chrono::{Datelike, Duration, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::Strings;
+
+pub const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum ScheduleAction {
+    /// Presence is only shown inside windows with this action.
+    #[default]
+    Enable,
+    Disable,
+    Privacy,
+}
+
+impl ScheduleAction {
+    pub const ALL: [ScheduleAction; 3] = [Self::Enable, Self::Disable, Self::Privacy];
+
+    pub fn label(&self, strings: &Strings) -> &'static str {
+        match self {
+            Self::Enable => strings.schedule_enable,
+            Self::Disable => strings.schedule_disable,
+            Self::Privacy => strings.schedule_privacy,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ScheduleOutcome {
+    #[default]
+    Normal,
+    Disabled,
+    Privacy,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScheduleRule {
+    pub enabled: bool,
+    pub action: ScheduleAction,
+    pub days: Vec<Weekday>,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl Default for ScheduleRule {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            action: ScheduleAction::default(),
+            days: WEEKDAYS[..5].to_vec(),
+            start: NaiveTime::from_hms_opt(9, 0, 0).unwrap_or_default(),
+            end: NaiveTime::from_hms_opt(17, 0, 0).unwrap_or_default(),
+        }
+    }
+}
+
+impl ScheduleRule {
+    /// Windows whose end is not after their start wrap past midnight, and
+    /// belong to the weekday they start on.
+    pub fn matches(&self, now: NaiveDateTime) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let time = now.time();
+        let today = now.weekday();
+        if self.start < self.end {
+            return self.days.contains(&today) && time >= self.start && time < self.end;
+        }
+        let yesterday = (now - Duration::days(1)).weekday();
+        (self.days.contains(&today) && time >= self.start)
+            || (self.days.contains(&yesterday) && time < self.end)
+    }
+}
+
+/// Resolves the rules at `now`. Disable wins over privacy, and when any
+/// enable windows exist, presence is disabled outside all of them.
+pub fn evaluate(rules: &[ScheduleRule], now: NaiveDateTime) -> ScheduleOutcome {
+    let active = |action| {
+        rules
+            .iter()
+            .filter(|r| r.action == action)
+            .any(|r| r.matches(now))
+    };
+    let has_enable_windows = rules
+        .iter()
+        .any(|r| r.enabled && r.action == ScheduleAction::Enable);
+
+    if active(ScheduleAction::Disable) || (has_enable_windows && !active(ScheduleAction::Enable)) {
+        ScheduleOutcome::Disabled
+    } else if active(ScheduleAction::Privacy) {
+        ScheduleOutcome::Privacy
+    } else {
+        ScheduleOutcome::Normal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    /// 2024-01-01 was a Monday.
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 1, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn rule(action: ScheduleAction, days: &[Weekday], start: u32, end: u32) -> ScheduleRule {
+        ScheduleRule {
+            enabled: true,
+            action,
+            days: days.to_vec(),
+            start: NaiveTime::from_hms_opt(start, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(end, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn same_day_window() {
+        let rule = rule(ScheduleAction::Disable, &[Weekday::Mon], 9, 17);
+        assert!(!rule.matches(at(1, 8, 59)));
+        assert!(rule.matches(at(1, 9, 0)));
+        assert!(rule.matches(at(1, 16, 59)));
+        assert!(!rule.matches(at(1, 17, 0)));
+        assert!(!rule.matches(at(2, 12, 0)));
+    }
+
+    #[test]
+    fn overnight_window_belongs_to_its_start_day() {
+        let rule = rule(ScheduleAction::Disable, &[Weekday::Mon], 22, 6);
+        assert!(rule.matches(at(1, 23, 0)));
+        // Tuesday morning is still Monday night's window.
+        assert!(rule.matches(at(2, 5, 59)));
+        assert!(!rule.matches(at(2, 6, 0)));
+        assert!(!rule.matches(at(2, 23, 0)));
+        // Monday morning would be Sunday night's window.
+        assert!(!rule.matches(at(1, 5, 0)));
+    }
+
+    #[test]
+    fn disabled_rule_never_matches() {
+        let mut rule = rule(ScheduleAction::Disable, &[Weekday::Mon], 9, 17);
+        rule.enabled = false;
+        assert!(!rule.matches(at(1, 12, 0)));
+    }
+
+    #[test]
+    fn disable_wins_over_privacy() {
+        let rules = [
+            rule(ScheduleAction::Privacy, &[Weekday::Mon], 9, 17),
+            rule(ScheduleAction::Disable, &[Weekday::Mon], 12, 13),
+        ];
+        assert_eq!(evaluate(&rules, at(1, 12, 30)), ScheduleOutcome::Disabled);
+        assert_eq!(evaluate(&rules, at(1, 10, 0)), ScheduleOutcome::Privacy);
+        assert_eq!(evaluate(&rules, at(1, 18, 0)), ScheduleOutcome::Normal);
+    }
+
+    #[test]
+    fn enable_windows_disable_everything_outside_them() {
+        let rules = [rule(ScheduleAction::Enable, &WEEKDAYS[..5], 9, 17)];
+        assert_eq!(evaluate(&rules, at(1, 10, 0)), ScheduleOutcome::Normal);
+        assert_eq!(evaluate(&rules, at(1, 18, 0)), ScheduleOutcome::Disabled);
+        assert_eq!(evaluate(&rules, at(6, 10, 0)), ScheduleOutcome::Disabled);
+    }
+
+    #[test]
+    fn no_rules_is_normal() {
+        assert_eq!(evaluate(&[], at(1, 3, 0)), ScheduleOutcome::Normal);
+    }
+
+    #[test]
+    fn partial_rule_fills_in_defaults() {
+        let rule: ScheduleRule =
+            serde_json::from_str(r#"{"action": "Disable", "start": "22:00:00"}"#).unwrap();
+        assert_eq!(
+            rule,
+            ScheduleRule {
+                action: ScheduleAction::Disable,
+                start: NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+                ..ScheduleRule::default()
+            }
+        );
+    }
+}
@@ -1,10 +1,47 @@
+use anyhow::{Context, Result, bail};
+use chrono::Local;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fs, path::PathBuf};
+use serde_json::{Value, json};
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::{self, File},
+    io::Write,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    sync::{
+        Mutex, OnceLock, RwLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::Duration,
+};
 
-use crate::log_debug;
+use crate::{
+    i18n::{self, Language, Strings},
+    log_debug, log_info, log_warn, policy, power,
+    schedule::ScheduleRule,
+};
 
 pub const FALLBACK_IMAGE: &str = "defaulticon";
 
+pub const SETTINGS_VERSION: u32 = 2;
+
+/// Longest activity name Discord accepts.
+pub const MAX_APP_NAME_LEN: usize = 128;
+
+/// Longest file alias, leaving room in Discord's 128 character details
+/// line for the "File: " prefix.
+pub const MAX_ALIAS_LEN: usize = 100;
+
+/// Entry `i` upgrades a settings document from version `i + 1` to `i + 2`.
+const MIGRATIONS: &[fn(&mut Value)] = &[migrate_v1_to_v2];
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+static NOTICE: Mutex<Option<Notice>> = Mutex::new(None);
+static CONFIG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub enum AppName {
     #[default]
@@ -13,51 +50,423 @@ pub enum AppName {
     Custom(String),
 }
 
-pub const STATE_ENTRIES: &[(&str, &str)] = &[
-    ("design", "Designing"),
-    ("whiteboard", "Whiteboarding"),
-    ("slides", "Presenting"),
-    ("sites", "Building a Site"),
-    ("buzz", "Buzzing"),
-    ("make", "Making"),
-    ("dev_mode", "Dev Mode"),
-    ("other", "Working"),
-    ("idle", "Idle"),
+/// Activity states that can have their own image, labelled through
+/// [`Strings::state`].
+pub const STATE_KEYS: &[&str] = &[
+    "design",
+    "whiteboard",
+    "slides",
+    "sites",
+    "buzz",
+    "make",
+    "dev_mode",
+    "other",
+    "idle",
 ];
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ImageOverride {
     pub enabled: bool,
     pub image_url: String,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Longest asset key or image URL Discord accepts.
+pub const MAX_IMAGE_LEN: usize = 256;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageKind {
+    /// An asset uploaded to the Discord application, referred to by name.
+    AssetKey,
+    Url,
+}
+
+/// Why Discord wouldn't display an image value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImageError {
+    TooLong,
+    Whitespace,
+    /// A link with a scheme other than http(s), such as `file`.
+    Scheme(String),
+    NoHost,
+    Invalid,
+}
+
+impl ImageError {
+    pub fn text(&self, strings: &Strings) -> String {
+        match self {
+            ImageError::TooLong => i18n::fill(strings.image_too_long, &[&MAX_IMAGE_LEN]),
+            ImageError::Whitespace => strings.image_whitespace.to_string(),
+            ImageError::Scheme(scheme) => i18n::fill(strings.image_scheme, &[scheme]),
+            ImageError::NoHost => strings.image_no_host.to_string(),
+            ImageError::Invalid => strings.image_invalid.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.text(&i18n::EN))
+    }
+}
+
+/// Tells Discord asset keys apart from image URLs, rejecting values Discord
+/// would silently replace with a blank image.
+pub fn check_image(value: &str) -> Result<ImageKind, ImageError> {
+    if value.is_empty() {
+        return Err(ImageError::Invalid);
+    }
+    if value.len() > MAX_IMAGE_LEN {
+        return Err(ImageError::TooLong);
+    }
+    if value.chars().any(char::is_whitespace) {
+        return Err(ImageError::Whitespace);
+    }
+    if let Some((scheme, rest)) = value.split_once("://") {
+        if !scheme.eq_ignore_ascii_case("http") && !scheme.eq_ignore_ascii_case("https") {
+            return Err(ImageError::Scheme(scheme.to_string()));
+        }
+        let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        if host.is_empty() {
+            return Err(ImageError::NoHost);
+        }
+        return Ok(ImageKind::Url);
+    }
+    // Media proxy references such as `mp:external/...` are passed through.
+    if value.starts_with("mp:")
+        || value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Ok(ImageKind::AssetKey);
+    }
+    Err(ImageError::Invalid)
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum PrivacyAction {
+    #[default]
+    HideFilename,
+    ClearPresence,
+}
+
+pub const DEFAULT_SENSITIVE_PROCESSES: &[&str] = &[
+    "zoom",
+    "zoom.us",
+    "teams",
+    "ms-teams",
+    "msteams",
+    "teams-for-linux",
+    "obs",
+    "obs64",
+    "webex",
+    "ciscowebexstart",
+];
+
+/// How one Figma file is shown, set from the tray's Recent Files menu.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FileRule {
+    /// The file key from the file's link, or its title when there is none.
+    pub file: String,
+    /// The title last seen, to tell rules apart in the settings window.
+    pub title: String,
+    pub hide_name: bool,
+    /// Shown instead of the title when not empty.
+    pub alias: String,
+    /// Never show the file at all, as if no file were open.
+    pub exclude: bool,
+}
+
+impl FileRule {
+    fn is_empty(&self) -> bool {
+        !self.hide_name && !self.exclude && self.alias.trim().is_empty()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrivacySettings {
+    pub enabled: bool,
+    pub action: PrivacyAction,
+    pub processes: Vec<String>,
+    pub files: Vec<FileRule>,
+}
+
+impl Default for PrivacySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            action: PrivacyAction::default(),
+            processes: DEFAULT_SENSITIVE_PROCESSES
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+            files: Vec::new(),
+        }
+    }
+}
+
+pub const FIGMA_POLLING_RANGE: RangeInclusive<u64> = 1..=60;
+pub const PRESENCE_UPDATE_RANGE: RangeInclusive<u64> = 5..=300;
+pub const IDLE_THRESHOLD_RANGE: RangeInclusive<u64> = 30..=3600;
+
+/// How much the battery saver stretches the polling intervals.
+pub const BATTERY_SAVER_FACTOR: u32 = 3;
+
+/// Timings of the worker threads, in seconds.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdvancedSettings {
+    pub figma_polling_secs: u64,
+    pub presence_update_secs: u64,
+    pub idle_threshold_secs: u64,
+    pub battery_saver: bool,
+}
+
+impl Default for AdvancedSettings {
+    fn default() -> Self {
+        Self {
+            figma_polling_secs: 5,
+            presence_update_secs: 15,
+            idle_threshold_secs: 300,
+            battery_saver: true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Intervals {
+    pub figma_polling: Duration,
+    pub presence_update: Duration,
+    pub idle_threshold: Duration,
+}
+
+impl AdvancedSettings {
+    /// The effective intervals, stretched by the battery saver when the
+    /// machine is on battery. The idle threshold is never stretched.
+    pub fn intervals(&self) -> Intervals {
+        let factor = if self.battery_saver && power::on_battery() {
+            BATTERY_SAVER_FACTOR
+        } else {
+            1
+        };
+        Intervals {
+            figma_polling: Duration::from_secs(self.figma_polling_secs) * factor,
+            presence_update: Duration::from_secs(self.presence_update_secs) * factor,
+            idle_threshold: Duration::from_secs(self.idle_threshold_secs),
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        for (name, value, range) in [
+            (
+                "figma_polling_secs",
+                self.figma_polling_secs,
+                FIGMA_POLLING_RANGE,
+            ),
+            (
+                "presence_update_secs",
+                self.presence_update_secs,
+                PRESENCE_UPDATE_RANGE,
+            ),
+            (
+                "idle_threshold_secs",
+                self.idle_threshold_secs,
+                IDLE_THRESHOLD_RANGE,
+            ),
+        ] {
+            if !range.contains(&value) {
+                bail!(
+                    "advanced.{name}: must be between {} and {}",
+                    range.start(),
+                    range.end()
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::System, Theme::Light, Theme::Dark];
+
+    pub fn label(self, strings: &Strings) -> &'static str {
+        match self {
+            Theme::System => strings.theme_system,
+            Theme::Light => strings.theme_light,
+            Theme::Dark => strings.theme_dark,
+        }
+    }
+}
+
+pub const UI_SCALE_RANGE: RangeInclusive<f32> = 0.75..=2.0;
+
+/// Look of the settings window.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Appearance {
+    pub theme: Theme,
+    pub ui_scale: f32,
+}
+
+impl Default for Appearance {
+    fn default() -> Self {
+        Self {
+            theme: Theme::System,
+            ui_scale: 1.0,
+        }
+    }
+}
+
+impl Appearance {
+    fn validate(&self) -> Result<()> {
+        if !UI_SCALE_RANGE.contains(&self.ui_scale) {
+            bail!(
+                "appearance.ui_scale: must be between {} and {}",
+                UI_SCALE_RANGE.start(),
+                UI_SCALE_RANGE.end()
+            );
+        }
+        Ok(())
+    }
+}
+
+pub const NOTIFICATION_INTERVAL_RANGE: RangeInclusive<u64> = 10..=3600;
+
+/// Which desktop notifications to show, and how often the same one may
+/// repeat.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub enabled: bool,
+    pub discord: bool,
+    pub figma: bool,
+    pub settings: bool,
+    pub updates: bool,
+    pub min_interval_secs: u64,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            discord: true,
+            figma: true,
+            settings: true,
+            updates: true,
+            min_interval_secs: 300,
+        }
+    }
+}
+
+impl NotificationSettings {
+    fn validate(&self) -> Result<()> {
+        if !NOTIFICATION_INTERVAL_RANGE.contains(&self.min_interval_secs) {
+            bail!(
+                "notifications.min_interval_secs: must be between {} and {}",
+                NOTIFICATION_INTERVAL_RANGE.start(),
+                NOTIFICATION_INTERVAL_RANGE.end()
+            );
+        }
+        Ok(())
+    }
+}
+
+pub const DEFAULT_PROFILE: &str = "Default";
+
+/// Snapshot of the per-profile fields of [`Settings`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub name: String,
+    pub default_image: String,
+    pub image_overrides: HashMap<String, ImageOverride>,
+    pub hide_filename: bool,
+    pub app_name: AppName,
+}
+
+fn default_profile_name() -> String {
+    DEFAULT_PROFILE.to_string()
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Settings {
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default)]
     pub default_image: String,
+    #[serde(default)]
     pub image_overrides: HashMap<String, ImageOverride>,
+    #[serde(default)]
     pub hide_filename: bool,
+    #[serde(default)]
     pub disable_idle: bool,
+    /// Mirrors whether the OS starts the app at login (see [`crate::autostart`]).
+    #[serde(default)]
+    pub launch_at_login: bool,
     #[serde(default)]
     pub app_name: AppName,
+    #[serde(default)]
+    pub privacy: PrivacySettings,
+    #[serde(default)]
+    pub schedule: Vec<ScheduleRule>,
+    #[serde(default)]
+    pub advanced: AdvancedSettings,
+    #[serde(default)]
+    pub appearance: Appearance,
+    #[serde(default)]
+    pub notifications: NotificationSettings,
+    /// `None` follows the OS language.
+    #[serde(default)]
+    pub language: Option<Language>,
+    #[serde(default = "default_profile_name")]
+    pub active_profile: String,
+    #[serde(default)]
+    pub profiles: Vec<Profile>,
 }
 
 impl Default for Settings {
     fn default() -> Self {
-        let overrides = STATE_ENTRIES
+        let overrides = STATE_KEYS
             .iter()
-            .map(|(key, _)| (key.to_string(), ImageOverride::default()))
+            .map(|key| (key.to_string(), ImageOverride::default()))
             .collect();
-        Self {
+        let mut settings = Self {
+            version: SETTINGS_VERSION,
             default_image: String::new(),
             image_overrides: overrides,
             hide_filename: false,
             disable_idle: false,
+            launch_at_login: false,
             app_name: AppName::default(),
-        }
+            privacy: PrivacySettings::default(),
+            schedule: Vec::new(),
+            advanced: AdvancedSettings::default(),
+            appearance: Appearance::default(),
+            notifications: NotificationSettings::default(),
+            language: None,
+            active_profile: default_profile_name(),
+            profiles: Vec::new(),
+        };
+        settings.ensure_active_profile();
+        settings
     }
 }
 
 impl Settings {
+    pub fn strings(&self) -> &'static Strings {
+        i18n::strings(self.language)
+    }
+
     pub fn resolved_app_name(&self) -> &str {
         match &self.app_name {
             AppName::Figma => "Figma",
@@ -66,9 +475,365 @@ impl Settings {
             AppName::Custom(s) => s,
         }
     }
+
+    pub fn file_rule(&self, file: &str) -> Option<&FileRule> {
+        self.privacy.files.iter().find(|rule| rule.file == file)
+    }
+
+    /// Changes the rule for `file`, creating it as needed and dropping it
+    /// again once it no longer changes anything.
+    pub fn update_file_rule(
+        &mut self,
+        file: &str,
+        title: &str,
+        change: impl FnOnce(&mut FileRule),
+    ) {
+        let files = &mut self.privacy.files;
+        let index = match files.iter().position(|rule| rule.file == file) {
+            Some(index) => index,
+            None => {
+                files.push(FileRule {
+                    file: file.to_string(),
+                    ..FileRule::default()
+                });
+                files.len() - 1
+            }
+        };
+        let rule = &mut files[index];
+        rule.title = title.to_string();
+        change(rule);
+        if rule.is_empty() {
+            files.remove(index);
+        }
+    }
+
+    /// Drops file rules that don't change anything, such as one added for
+    /// an alias that was never entered, and aliases that repeat the title.
+    pub fn tidy_file_rules(&mut self) {
+        for rule in &mut self.privacy.files {
+            if rule.alias.trim() == rule.title.trim() {
+                rule.alias.clear();
+            }
+        }
+        self.privacy.files.retain(|rule| !rule.is_empty());
+    }
+}
+
+pub const CONFIG_DIR_ENV: &str = "FIGMA_RP_CONFIG_DIR";
+pub const PORTABLE_MARKER: &str = "portable.txt";
+
+/// Picks the directory for settings and state: an explicit `--config-dir`,
+/// then the environment variable, then `data/` next to an executable that
+/// has a [`PORTABLE_MARKER`] beside it, then the OS config directory.
+pub fn resolve_config_dir(
+    flag: Option<PathBuf>,
+    env: Option<PathBuf>,
+    exe_dir: Option<&Path>,
+    os_config: Option<PathBuf>,
+) -> PathBuf {
+    let given = |dir: Option<PathBuf>| dir.filter(|d| !d.as_os_str().is_empty());
+    if let Some(dir) = given(flag).or_else(|| given(env)) {
+        return dir;
+    }
+    if let Some(exe_dir) = exe_dir
+        && exe_dir.join(PORTABLE_MARKER).is_file()
+    {
+        return exe_dir.join("data");
+    }
+    os_config
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("dyl-figma-discord-rp")
+}
+
+fn current_config_dir(flag: Option<PathBuf>) -> PathBuf {
+    let exe = std::env::current_exe().ok();
+    resolve_config_dir(
+        flag,
+        std::env::var_os(CONFIG_DIR_ENV).map(PathBuf::from),
+        exe.as_deref().and_then(Path::parent),
+        dirs::config_dir(),
+    )
+}
+
+/// Must be called before anything reads settings to take effect.
+pub fn set_config_dir(flag: Option<PathBuf>) {
+    let _ = CONFIG_DIR.set(current_config_dir(flag));
+}
+
+pub fn config_dir() -> PathBuf {
+    #[cfg(test)]
+    if let Some(dir) = test_env::dir() {
+        return dir;
+    }
+    CONFIG_DIR.get_or_init(|| current_config_dir(None)).clone()
+}
+
+/// Process-wide state for tests that touch the config dir or the policy.
+#[cfg(test)]
+pub mod test_env {
+    use std::{
+        path::{Path, PathBuf},
+        sync::{Mutex, MutexGuard},
+    };
+
+    use serde_json::Value;
+
+    use crate::policy;
+
+    static SERIAL: Mutex<()> = Mutex::new(());
+    static DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+    pub(super) fn dir() -> Option<PathBuf> {
+        DIR.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Points [`super::config_dir`] at a fresh temporary directory, with no
+    /// policy, until dropped. Only one exists at a time, so tests holding
+    /// one run one after another.
+    pub struct TestEnv {
+        dir: tempfile::TempDir,
+        _serial: MutexGuard<'static, ()>,
+    }
+
+    impl TestEnv {
+        pub fn new() -> Self {
+            let serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+            let dir = tempfile::tempdir().unwrap();
+            *DIR.lock().unwrap_or_else(|e| e.into_inner()) = Some(dir.path().to_path_buf());
+            policy::set_for_test(None);
+            super::take_notice();
+            Self {
+                dir,
+                _serial: serial,
+            }
+        }
+
+        /// Like [`TestEnv::new`], with `policy` in place of the policy file.
+        pub fn with_policy(policy: Value) -> Self {
+            let env = Self::new();
+            policy::set_for_test(Some(policy));
+            env
+        }
+
+        pub fn path(&self) -> &Path {
+            self.dir.path()
+        }
+    }
+
+    impl Drop for TestEnv {
+        fn drop(&mut self) {
+            *DIR.lock().unwrap_or_else(|e| e.into_inner()) = None;
+            policy::set_for_test(None);
+        }
+    }
+}
+
+impl Settings {
+    fn capture_profile(&self, name: &str) -> Profile {
+        Profile {
+            name: name.to_string(),
+            default_image: self.default_image.clone(),
+            image_overrides: self.image_overrides.clone(),
+            hide_filename: self.hide_filename,
+            app_name: self.app_name.clone(),
+        }
+    }
+
+    fn apply_profile(&mut self, profile: &Profile) {
+        self.default_image = profile.default_image.clone();
+        self.image_overrides = profile.image_overrides.clone();
+        self.hide_filename = profile.hide_filename;
+        self.app_name = profile.app_name.clone();
+        self.active_profile = profile.name.clone();
+        self.apply_policy();
+    }
+
+    /// Overlays the managed policy (see [`policy`]), which always wins over
+    /// the user's own values.
+    fn apply_policy(&mut self) {
+        let Some(policy) = policy::get() else {
+            return;
+        };
+        let Ok(mut doc) = serde_json::to_value(&*self) else {
+            return;
+        };
+        policy::merge(&mut doc, policy);
+        match serde_json::from_value(doc) {
+            Ok(settings) => *self = settings,
+            Err(e) => {
+                log_warn!("policy", "Failed to apply: {e}");
+            }
+        }
+    }
+
+    /// The settings as a JSON document, with the active profile up to date.
+    /// Applies the edits that turned `before` into `after`, leaving fields
+    /// they didn't touch as they are here.
+    pub fn apply_changes(&mut self, before: &Settings, after: &Settings) -> Result<()> {
+        let mut doc = self.to_value()?;
+        copy_changes(&mut doc, &before.to_value()?, &after.to_value()?);
+        *self = serde_json::from_value(doc)?;
+        self.normalize();
+        Ok(())
+    }
+
+    pub fn to_value(&self) -> Result<Value> {
+        let mut settings = self.clone();
+        settings.sync_active_profile();
+        Ok(serde_json::to_value(settings)?)
+    }
+
+    /// Fills in what older or hand-edited files may lack: an override entry
+    /// for every state and an entry for the active profile.
+    pub fn normalize(&mut self) {
+        for key in STATE_KEYS {
+            self.image_overrides.entry(key.to_string()).or_default();
+        }
+        self.ensure_active_profile();
+        self.apply_policy();
+    }
+
+    fn ensure_active_profile(&mut self) {
+        if !self.profiles.iter().any(|p| p.name == self.active_profile) {
+            let profile = self.capture_profile(&self.active_profile);
+            self.profiles.push(profile);
+        }
+    }
+
+    /// Writes the live per-profile fields back into the active profile entry.
+    pub fn sync_active_profile(&mut self) {
+        self.ensure_active_profile();
+        let profile = self.capture_profile(&self.active_profile);
+        if let Some(entry) = self.profiles.iter_mut().find(|p| p.name == profile.name) {
+            *entry = profile;
+        }
+    }
+
+    pub fn profile_names(&self) -> Vec<String> {
+        self.profiles.iter().map(|p| p.name.clone()).collect()
+    }
+
+    pub fn switch_profile(&mut self, name: &str) -> bool {
+        let Some(target) = self.profiles.iter().find(|p| p.name == name).cloned() else {
+            return false;
+        };
+        self.sync_active_profile();
+        self.apply_profile(&target);
+        true
+    }
+
+    pub fn unique_profile_name(&self, base: &str) -> String {
+        let mut name = base.to_string();
+        let mut n = 2;
+        while self.profiles.iter().any(|p| p.name == name) {
+            name = format!("{base} {n}");
+            n += 1;
+        }
+        name
+    }
+
+    /// Adds a profile with default values, or a copy of the active one when
+    /// `duplicate` is set, and switches to it.
+    pub fn create_profile(&mut self, base: &str, duplicate: bool) -> String {
+        let name = self.unique_profile_name(base);
+        let profile = if duplicate {
+            self.capture_profile(&name)
+        } else {
+            Self::default().capture_profile(&name)
+        };
+        self.profiles.push(profile);
+        self.switch_profile(&name);
+        name
+    }
+
+    pub fn rename_profile(&mut self, old: &str, new: &str) -> bool {
+        let new = new.trim();
+        if new.is_empty() || self.profiles.iter().any(|p| p.name == new) {
+            return false;
+        }
+        let Some(profile) = self.profiles.iter_mut().find(|p| p.name == old) else {
+            return false;
+        };
+        profile.name = new.to_string();
+        if self.active_profile == old {
+            self.active_profile = new.to_string();
+        }
+        true
+    }
+
+    pub fn delete_profile(&mut self, name: &str) -> bool {
+        if self.profiles.len() <= 1 {
+            return false;
+        }
+        let count = self.profiles.len();
+        self.profiles.retain(|p| p.name != name);
+        if self.profiles.len() == count {
+            return false;
+        }
+        if self.active_profile == name {
+            let next = self.profiles[0].clone();
+            self.apply_profile(&next);
+        }
+        true
+    }
 }
 
 impl Settings {
+    /// Checks the rules the settings window enforces; shared with the CLI and
+    /// the file watcher so hand edits can't bypass them.
+    pub fn validate(&self) -> Result<()> {
+        if let AppName::Custom(name) = &self.app_name
+            && name.chars().count() > MAX_APP_NAME_LEN
+        {
+            bail!("app_name: custom name must be at most {MAX_APP_NAME_LEN} characters");
+        }
+        if let Some(key) = self
+            .image_overrides
+            .keys()
+            .find(|k| !STATE_KEYS.contains(&k.as_str()))
+        {
+            bail!("image_overrides: unknown state \"{key}\"");
+        }
+        if !self.default_image.is_empty()
+            && let Err(e) = check_image(&self.default_image)
+        {
+            bail!("default_image: {e}");
+        }
+        for (key, entry) in &self.image_overrides {
+            if !entry.image_url.is_empty()
+                && let Err(e) = check_image(&entry.image_url)
+            {
+                bail!("image_overrides.{key}.image_url: {e}");
+            }
+        }
+        self.advanced.validate()?;
+        self.appearance.validate()?;
+        self.notifications.validate()?;
+        if self.privacy.processes.iter().any(|p| p.trim().is_empty()) {
+            bail!("privacy.processes: process names must not be empty");
+        }
+        for (i, rule) in self.privacy.files.iter().enumerate() {
+            if rule.file.trim().is_empty() {
+                bail!("privacy.files: file must not be empty");
+            }
+            if self.privacy.files[..i].iter().any(|r| r.file == rule.file) {
+                bail!("privacy.files: duplicate rule for \"{}\"", rule.file);
+            }
+            if rule.alias.chars().count() > MAX_ALIAS_LEN {
+                bail!("privacy.files: alias must be at most {MAX_ALIAS_LEN} characters");
+            }
+        }
+        for (i, profile) in self.profiles.iter().enumerate() {
+            if profile.name.trim().is_empty() {
+                bail!("profiles: profile names must not be empty");
+            }
+            if self.profiles[..i].iter().any(|p| p.name == profile.name) {
+                bail!("profiles: duplicate profile \"{}\"", profile.name);
+            }
+        }
+        Ok(())
+    }
+
     pub fn image_url_for_state(&self, state_key: &str) -> &str {
         if let Some(ov) = self.image_overrides.get(state_key)
             && ov.enabled
@@ -84,30 +849,636 @@ impl Settings {
     }
 
     fn path() -> PathBuf {
-        let config = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
-        config.join("dyl-figma-discord-rp").join("settings.json")
+        config_dir().join("settings.json")
+    }
+
+    fn parse(data: &str) -> Result<Self> {
+        let mut doc: Value = serde_json::from_str(data)?;
+        let version = doc
+            .get("version")
+            .and_then(Value::as_u64)
+            .map_or(1, |v| v as u32);
+        if version > SETTINGS_VERSION {
+            log_warn!(
+                "settings",
+                "File is from a newer version (v{version}), it's backed up before the next save"
+            );
+        }
+        for (from, migrate) in MIGRATIONS
+            .iter()
+            .enumerate()
+            .map(|(i, m)| (i as u32 + 1, m))
+            .skip_while(|(from, _)| *from < version)
+        {
+            log_info!("settings", "Migrating from v{from} to v{}", from + 1);
+            migrate(&mut doc);
+        }
+        let mut settings: Self = serde_json::from_value(doc)?;
+        settings.version = SETTINGS_VERSION;
+        settings.normalize();
+        Ok(settings)
+    }
+
+    /// Copies an unreadable settings file aside and replaces it with defaults,
+    /// so the next load doesn't trip over (and back up) the same file again.
+    fn recover(path: &Path, err: &anyhow::Error) -> Self {
+        let backup = path.with_file_name(format!(
+            "settings.json.bak-{}",
+            Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        let mut settings = Self::default();
+        settings.apply_policy();
+        match fs::copy(path, &backup) {
+            Ok(_) => {
+                log_warn!(
+                    "settings",
+                    "Failed to read settings ({err}), backed up to {}",
+                    backup.display()
+                );
+                if let Err(e) = settings.save() {
+                    log_warn!("settings", "Failed to write defaults: {e:#}");
+                }
+                set_notice(Notice::Reset {
+                    backup: backup_name(&backup),
+                });
+            }
+            Err(e) => {
+                log_warn!(
+                    "settings",
+                    "Failed to read settings ({err}) and to back them up ({e}), using defaults"
+                );
+                set_notice(Notice::Unreadable);
+            }
+        }
+        settings
     }
 
     pub fn load() -> Self {
         let path = Self::path();
         log_debug!("settings", "Loading from {}", path.display());
-        match fs::read_to_string(&path) {
-            Ok(data) => serde_json::from_str(&data).unwrap_or_default(),
+        match read_file(&path) {
+            Ok(data) => Self::parse(&data).unwrap_or_else(|e| Self::recover(&path, &e)),
             Err(_) => {
                 log_debug!("settings", "No settings file found, using defaults");
-                Self::default()
+                let mut settings = Self::default();
+                settings.apply_policy();
+                settings
             }
         }
     }
 
-    pub fn save(&self) {
+    /// Reads the file as it is now, for callers already holding the lock.
+    fn load_locked(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(data) => {
+                Self::parse(&data).with_context(|| format!("failed to read {}", path.display()))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let mut settings = Self::default();
+                settings.apply_policy();
+                Ok(settings)
+            }
+            Err(e) => Err(e).with_context(|| format!("failed to read {}", path.display())),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let _lock = lock(true)?;
+        self.save_locked()
+    }
+
+    fn save_locked(&self) -> Result<()> {
         let path = Self::path();
-        if let Some(parent) = path.parent() {
-            let _ = fs::create_dir_all(parent);
+        let mut doc = self.to_value()?;
+        if let Some(policy) = policy::get() {
+            let on_disk = fs::read_to_string(&path)
+                .ok()
+                .and_then(|data| serde_json::from_str::<Value>(&data).ok());
+            strip_policy(&mut doc, policy, on_disk.as_ref());
+        }
+        let data = serde_json::to_string_pretty(&doc)?;
+        back_up_newer_version(&path)?;
+        write_atomic(&path, data.as_bytes())
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        log_debug!("settings", "Saved to {}", path.display());
+        Ok(())
+    }
+}
+
+/// Loads the settings file, applies `edit` and saves the result, holding the
+/// lock throughout so a change another process saved in between isn't
+/// written over. Returns the settings as saved.
+pub fn update(edit: impl FnOnce(&mut Settings) -> Result<()>) -> Result<Settings> {
+    let _lock = lock(true)?;
+    let mut settings = Settings::load_locked(&Settings::path())?;
+    edit(&mut settings)?;
+    settings.save_locked()?;
+    Ok(settings)
+}
+
+/// Copies what changed between `before` and `after` onto `target`, field by
+/// field, so fields changed elsewhere in the meantime keep their new values.
+/// Lists count as one field.
+fn copy_changes(target: &mut Value, before: &Value, after: &Value) {
+    match (target, after) {
+        (Value::Object(target), Value::Object(after)) => {
+            for (key, value) in after {
+                let old = before.get(key).unwrap_or(&Value::Null);
+                if old != value {
+                    copy_changes(target.entry(key.clone()).or_insert(Value::Null), old, value);
+                }
+            }
+        }
+        (target, after) => {
+            if before != after {
+                *target = after.clone();
+            }
+        }
+    }
+}
+
+/// Puts the user's own values from `on_disk` back where the policy overlaid
+/// them, so managed values never end up in the file and don't outlive the
+/// policy. Profiles hold the same per-profile keys as the top level.
+fn strip_policy(doc: &mut Value, policy: &Value, on_disk: Option<&Value>) {
+    policy::unmerge(doc, policy, on_disk);
+    let Some(profiles) = doc.get_mut("profiles").and_then(Value::as_array_mut) else {
+        return;
+    };
+    for profile in profiles {
+        let original = on_disk
+            .and_then(|d| d.get("profiles")?.as_array())
+            .and_then(|list| {
+                list.iter()
+                    .find(|p| p.get("name").is_some() && p.get("name") == profile.get("name"))
+            });
+        policy::unmerge(profile, policy, original);
+    }
+}
+
+/// A file written by a newer version holds fields this one doesn't know and
+/// would drop, so it's copied aside before it's first saved over.
+fn back_up_newer_version(path: &Path) -> Result<()> {
+    let Ok(data) = fs::read_to_string(path) else {
+        return Ok(());
+    };
+    let version = serde_json::from_str::<Value>(&data)
+        .ok()
+        .and_then(|doc| doc.get("version")?.as_u64())
+        .unwrap_or(0);
+    if version <= u64::from(SETTINGS_VERSION) {
+        return Ok(());
+    }
+    let backup = path.with_file_name(format!("settings.json.v{version}.bak"));
+    fs::copy(path, &backup).with_context(|| format!("failed to back up {}", path.display()))?;
+    log_warn!(
+        "settings",
+        "Saving over settings from v{version}, backed up to {}",
+        backup.display()
+    );
+    set_notice(Notice::NewerVersion {
+        backup: backup_name(&backup),
+    });
+    Ok(())
+}
+
+/// Takes the advisory lock shared by the tray process and the settings
+/// window, so neither reads a file the other is halfway through replacing.
+/// The lock is released when the returned file is dropped.
+fn lock(exclusive: bool) -> Result<File> {
+    let dir = config_dir();
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let file = File::create(dir.join("settings.lock")).context("failed to open lock file")?;
+    if exclusive {
+        file.lock()
+    } else {
+        file.lock_shared()
+    }
+    .context("failed to lock settings")?;
+    Ok(file)
+}
+
+fn read_file(path: &Path) -> std::io::Result<String> {
+    let lock = lock(false);
+    if let Err(e) = &lock {
+        log_warn!("settings", "Reading without lock: {e:#}");
+    }
+    fs::read_to_string(path)
+}
+
+/// Writes `data` to a temporary file next to `path`, syncs it and renames it
+/// over `path`, so a crash mid-write never leaves a truncated file behind.
+pub fn write_atomic(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    fs::create_dir_all(dir)?;
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    file.write_all(data)?;
+    file.as_file().sync_all()?;
+    file.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+/// A settings problem worth showing to the user.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Notice {
+    /// The file couldn't be read and was replaced with defaults.
+    Reset { backup: String },
+    /// The file couldn't be read nor backed up; defaults are used in memory.
+    Unreadable,
+    /// A file written by a newer version was backed up before saving over it.
+    NewerVersion { backup: String },
+    /// An external edit was rejected, with the reason.
+    InvalidEdit(String),
+}
+
+impl Notice {
+    pub fn text(&self, strings: &Strings) -> String {
+        match self {
+            Notice::Reset { backup } => i18n::fill(strings.notice_reset, &[backup]),
+            Notice::Unreadable => strings.notice_unreadable.to_string(),
+            Notice::NewerVersion { backup } => i18n::fill(strings.notice_newer_version, &[backup]),
+            Notice::InvalidEdit(reason) => i18n::fill(strings.notice_invalid_edit, &[reason]),
         }
-        if let Ok(data) = serde_json::to_string_pretty(self) {
-            let _ = fs::write(&path, data);
-            log_debug!("settings", "Saved to {}", path.display());
+    }
+}
+
+fn backup_name(backup: &Path) -> String {
+    backup
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn set_notice(notice: Notice) {
+    *NOTICE.lock().unwrap() = Some(notice);
+}
+
+/// Returns the last settings problem worth showing to the user (a recovered
+/// file or a rejected external edit), if any, once.
+pub fn take_notice() -> Option<Notice> {
+    NOTICE.lock().unwrap().take()
+}
+
+/// Re-reads the settings file into `settings`, keeping them as they are
+/// when the file is invalid. Returns whether anything changed.
+pub fn reload(settings: &RwLock<Settings>) -> Result<bool> {
+    let path = Settings::path();
+    let data = read_file(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    let new = Settings::parse(&data)?;
+    new.validate()?;
+    let mut s = settings.write().unwrap();
+    if *s == new {
+        return Ok(false);
+    }
+    *s = new;
+    Ok(true)
+}
+
+/// Polls the settings file and applies valid external edits to `settings`.
+/// Invalid edits are rejected and reported, keeping the current settings.
+/// `on_change` runs after either, so the tray can catch up.
+pub fn watch(settings: &RwLock<Settings>, running: &AtomicBool, on_change: impl Fn()) {
+    let path = Settings::path();
+    let modified = || fs::metadata(&path).and_then(|m| m.modified()).ok();
+    let mut last_modified = modified();
+
+    while running.load(Ordering::Relaxed) {
+        thread::sleep(WATCH_INTERVAL);
+        let current = modified();
+        if current.is_none() || current == last_modified {
+            continue;
+        }
+        last_modified = current;
+
+        match reload(settings) {
+            Ok(true) => {
+                log_info!("settings", "Reloaded after external change");
+                on_change();
+            }
+            Ok(false) => {}
+            Err(e) => {
+                log_warn!("settings", "Ignored invalid edit: {e:#}");
+                set_notice(Notice::InvalidEdit(e.to_string()));
+                on_change();
+            }
+        }
+    }
+}
+
+/// v1 had no profiles, so seed the default one from the top-level fields.
+fn migrate_v1_to_v2(doc: &mut Value) {
+    let Some(obj) = doc.as_object_mut() else {
+        return;
+    };
+    if !obj.contains_key("profiles") {
+        let mut profile = json!({ "name": DEFAULT_PROFILE });
+        for key in [
+            "default_image",
+            "image_overrides",
+            "hide_filename",
+            "app_name",
+        ] {
+            if let Some(value) = obj.get(key) {
+                profile[key] = value.clone();
+            }
         }
+        obj.insert("profiles".to_string(), json!([profile]));
+        obj.insert("active_profile".to_string(), json!(DEFAULT_PROFILE));
+    }
+    obj.insert("version".to_string(), json!(2));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn os_config() -> Option<PathBuf> {
+        Some(PathBuf::from("/os-config"))
+    }
+
+    fn portable_dir() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(PORTABLE_MARKER), "").unwrap();
+        dir
+    }
+
+    #[test]
+    fn flag_wins_over_everything() {
+        let exe_dir = portable_dir();
+        let dir = resolve_config_dir(
+            Some(PathBuf::from("/flag")),
+            Some(PathBuf::from("/env")),
+            Some(exe_dir.path()),
+            os_config(),
+        );
+        assert_eq!(dir, PathBuf::from("/flag"));
+    }
+
+    #[test]
+    fn env_wins_over_portable_marker() {
+        let exe_dir = portable_dir();
+        let dir = resolve_config_dir(
+            None,
+            Some(PathBuf::from("/env")),
+            Some(exe_dir.path()),
+            os_config(),
+        );
+        assert_eq!(dir, PathBuf::from("/env"));
+    }
+
+    #[test]
+    fn portable_marker_wins_over_os_config() {
+        let exe_dir = portable_dir();
+        let dir = resolve_config_dir(None, None, Some(exe_dir.path()), os_config());
+        assert_eq!(dir, exe_dir.path().join("data"));
+    }
+
+    #[test]
+    fn os_config_without_marker() {
+        let exe_dir = tempfile::tempdir().unwrap();
+        let dir = resolve_config_dir(None, None, Some(exe_dir.path()), os_config());
+        assert_eq!(dir, PathBuf::from("/os-config/dyl-figma-discord-rp"));
+    }
+
+    #[test]
+    fn empty_values_are_ignored() {
+        let empty = || Some(PathBuf::new());
+        let dir = resolve_config_dir(empty(), Some(PathBuf::from("/env")), None, os_config());
+        assert_eq!(dir, PathBuf::from("/env"));
+        let dir = resolve_config_dir(None, empty(), None, os_config());
+        assert_eq!(dir, PathBuf::from("/os-config/dyl-figma-discord-rp"));
+        let exe_dir = portable_dir();
+        let dir = resolve_config_dir(empty(), empty(), Some(exe_dir.path()), os_config());
+        assert_eq!(dir, exe_dir.path().join("data"));
+    }
+
+    #[test]
+    fn write_atomic_replaces_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("file.json");
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        let leftovers = fs::read_dir(path.parent().unwrap()).unwrap().count();
+        assert_eq!(leftovers, 1, "temporary files were left behind");
+    }
+
+    #[test]
+    fn write_atomic_keeps_the_old_file_on_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.json");
+        fs::create_dir(&path).unwrap();
+        assert!(write_atomic(&path, b"data").is_err());
+        assert!(path.is_dir());
+    }
+
+    #[test]
+    fn lock_excludes_readers_while_writing() {
+        let env = test_env::TestEnv::new();
+        let held = lock(true).unwrap();
+        let other = File::open(env.path().join("settings.lock")).unwrap();
+        assert!(matches!(
+            other.try_lock_shared(),
+            Err(fs::TryLockError::WouldBlock)
+        ));
+        drop(held);
+        other.try_lock_shared().unwrap();
+    }
+
+    #[test]
+    fn save_reports_write_failures() {
+        let env = test_env::TestEnv::new();
+        fs::create_dir(env.path().join("settings.json")).unwrap();
+        assert!(Settings::default().save().is_err());
+        assert!(update(|_| Ok(())).is_err());
+    }
+
+    #[test]
+    fn update_keeps_changes_saved_since_loading() {
+        let _env = test_env::TestEnv::new();
+        update(|s| {
+            s.hide_filename = true;
+            Ok(())
+        })
+        .unwrap();
+        update(|s| {
+            s.disable_idle = true;
+            Ok(())
+        })
+        .unwrap();
+        let saved = Settings::load();
+        assert!(saved.hide_filename);
+        assert!(saved.disable_idle);
+    }
+
+    #[test]
+    fn update_leaves_the_file_alone_when_the_edit_fails() {
+        let env = test_env::TestEnv::new();
+        Settings::default().save().unwrap();
+        let before = fs::read_to_string(env.path().join("settings.json")).unwrap();
+        let result = update(|s| {
+            s.hide_filename = true;
+            bail!("rejected")
+        });
+        assert!(result.is_err());
+        let after = fs::read_to_string(env.path().join("settings.json")).unwrap();
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    fn apply_changes_only_copies_edited_fields() {
+        let before = Settings::default();
+        let after = Settings {
+            disable_idle: true,
+            ..before.clone()
+        };
+        let mut current = Settings {
+            hide_filename: true,
+            ..before.clone()
+        };
+        current.apply_changes(&before, &after).unwrap();
+        assert!(current.disable_idle);
+        assert!(current.hide_filename);
+    }
+
+    #[test]
+    fn v1_file_is_migrated() {
+        let env = test_env::TestEnv::new();
+        let path = env.path().join("settings.json");
+        fs::write(
+            &path,
+            r#"{"default_image": "my_key", "hide_filename": true}"#,
+        )
+        .unwrap();
+        let settings = Settings::load();
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(settings.active_profile, DEFAULT_PROFILE);
+        let profile = &settings.profiles[0];
+        assert_eq!(profile.name, DEFAULT_PROFILE);
+        assert_eq!(profile.default_image, "my_key");
+        assert!(profile.hide_filename);
+
+        settings.save().unwrap();
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["version"], json!(SETTINGS_VERSION));
+    }
+
+    #[test]
+    fn corrupt_file_is_backed_up_before_defaults_are_written() {
+        let env = test_env::TestEnv::new();
+        let path = env.path().join("settings.json");
+        fs::write(&path, "{ not json").unwrap();
+        assert_eq!(Settings::load(), Settings::default());
+
+        let Some(Notice::Reset { backup }) = take_notice() else {
+            panic!("no reset notice");
+        };
+        assert_eq!(
+            fs::read_to_string(env.path().join(backup)).unwrap(),
+            "{ not json"
+        );
+        let saved = fs::read_to_string(&path).unwrap();
+        assert!(Settings::parse(&saved).is_ok());
+    }
+
+    #[test]
+    fn newer_version_is_backed_up_before_saving_over_it() {
+        let env = test_env::TestEnv::new();
+        let path = env.path().join("settings.json");
+        let newer = r#"{"version": 99, "hide_filename": true, "from_the_future": 1}"#;
+        fs::write(&path, newer).unwrap();
+        let settings = Settings::load();
+        assert!(settings.hide_filename);
+        assert_eq!(take_notice(), None);
+
+        settings.save().unwrap();
+        assert_eq!(
+            take_notice(),
+            Some(Notice::NewerVersion {
+                backup: "settings.json.v99.bak".to_string()
+            })
+        );
+        let backup = env.path().join("settings.json.v99.bak");
+        assert_eq!(fs::read_to_string(backup).unwrap(), newer);
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["version"], json!(SETTINGS_VERSION));
+    }
+
+    #[test]
+    fn check_image_accepts_urls() {
+        for url in [
+            "https://example.com/icon.png",
+            "http://example.com",
+            "HTTPS://example.com?size=512",
+        ] {
+            assert_eq!(check_image(url), Ok(ImageKind::Url), "{url}");
+        }
+    }
+
+    #[test]
+    fn check_image_accepts_asset_keys() {
+        for key in [
+            "figma",
+            "figma_logo-2",
+            "mp:external/abc/https/example.com/a.png",
+        ] {
+            assert_eq!(check_image(key), Ok(ImageKind::AssetKey), "{key}");
+        }
+    }
+
+    #[test]
+    fn check_image_rejects_other_schemes() {
+        assert_eq!(
+            check_image("file:///tmp/icon.png"),
+            Err(ImageError::Scheme("file".to_string()))
+        );
+        assert_eq!(
+            check_image("ftp://example.com/icon.png"),
+            Err(ImageError::Scheme("ftp".to_string()))
+        );
+        assert_eq!(check_image("https:///icon.png"), Err(ImageError::NoHost));
+    }
+
+    #[test]
+    fn check_image_rejects_bad_characters_and_length() {
+        assert_eq!(check_image("my icon"), Err(ImageError::Whitespace));
+        assert_eq!(check_image("icon.png"), Err(ImageError::Invalid));
+        assert_eq!(check_image("icône"), Err(ImageError::Invalid));
+        let long = format!("https://example.com/{}", "a".repeat(MAX_IMAGE_LEN));
+        assert_eq!(check_image(&long), Err(ImageError::TooLong));
+        let longest = "a".repeat(MAX_IMAGE_LEN);
+        assert_eq!(check_image(&longest), Ok(ImageKind::AssetKey));
+    }
+
+    #[test]
+    fn check_image_rejects_empty_input() {
+        assert_eq!(check_image(""), Err(ImageError::Invalid));
+    }
+
+    #[test]
+    fn tidy_file_rules_drops_blank_rules_and_title_aliases() {
+        let rule = |file: &str, alias: &str, hide_name| FileRule {
+            file: file.to_string(),
+            title: "Landing page".to_string(),
+            alias: alias.to_string(),
+            hide_name,
+            exclude: false,
+        };
+        let mut settings = Settings::default();
+        settings.privacy.files = vec![
+            rule("blank", "", false),
+            rule("title", " Landing page ", false),
+            rule("hidden", "Landing page", true),
+            rule("alias", "Client work", false),
+        ];
+        settings.tidy_file_rules();
+        assert_eq!(
+            settings.privacy.files,
+            [
+                rule("hidden", "", true),
+                rule("alias", "Client work", false)
+            ]
+        );
     }
 }
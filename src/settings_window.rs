@@ -1,6 +1,20 @@
-use crate::settings::{AppName, STATE_ENTRIES, Settings};
+use crate::bundle::{self, Change};
+use crate::i18n::{Language, Strings, fill};
+use crate::policy;
+use crate::preview::{ImageProbes, Preview, ProbeState};
+use crate::schedule::{ScheduleAction, ScheduleRule, WEEKDAYS};
+use crate::settings::{
+    self, AppName, Appearance, FIGMA_POLLING_RANGE, FileRule, IDLE_THRESHOLD_RANGE, ImageKind,
+    MAX_ALIAS_LEN, Notice, PRESENCE_UPDATE_RANGE, PrivacyAction, STATE_KEYS, Settings, Theme,
+    UI_SCALE_RANGE, check_image,
+};
+use crate::tray::{self, TrayEvent};
+use chrono::{NaiveTime, Timelike};
 use eframe::egui;
+use serde::{Deserialize, Serialize};
 use std::{
+    fs,
+    path::PathBuf,
     process::Command,
     sync::{
         Arc, RwLock,
@@ -8,8 +22,18 @@ use std::{
     },
     thread,
 };
+use winit::event_loop::EventLoopProxy;
+
+mod embedded;
+
+pub use embedded::EmbeddedWindow;
 
-pub fn open(settings: Arc<RwLock<Settings>>, open_flag: Arc<AtomicBool>) {
+pub fn open(
+    settings: Arc<RwLock<Settings>>,
+    open_flag: Arc<AtomicBool>,
+    proxy: EventLoopProxy<TrayEvent>,
+    alias: Option<(String, String)>,
+) {
     if open_flag.swap(true, Ordering::Relaxed) {
         return;
     }
@@ -24,8 +48,16 @@ pub fn open(settings: Arc<RwLock<Settings>>, open_flag: Arc<AtomicBool>) {
             }
         };
 
-        let status: std::io::Result<std::process::ExitStatus> = Command::new(&exe)
+        let mut command = Command::new(&exe);
+        command
             .arg("--settings")
+            .arg("--standalone")
+            .arg("--config-dir")
+            .arg(settings::config_dir());
+        if let Some((file, title)) = &alias {
+            command.arg("--edit-alias").arg(file).arg(title);
+        }
+        let status: std::io::Result<std::process::ExitStatus> = command
             .stdin(std::process::Stdio::null())
             .stdout(std::process::Stdio::null())
             .stderr(std::process::Stdio::null())
@@ -35,6 +67,7 @@ pub fn open(settings: Arc<RwLock<Settings>>, open_flag: Arc<AtomicBool>) {
             Ok(s) => {
                 let reloaded = Settings::load();
                 *settings.write().unwrap() = reloaded;
+                tray::notify(&proxy);
                 if !s.success() {
                     eprintln!("[settings] window exited with {s}");
                 }
@@ -58,12 +91,55 @@ fn load_icon() -> egui::IconData {
     }
 }
 
+const DEFAULT_SIZE: [f32; 2] = [520.0, 620.0];
+const MIN_SIZE: [f32; 2] = [420.0, 400.0];
+
+/// Window geometry kept next to the settings file, separate from them so a
+/// resize never counts as an unsaved change.
+#[derive(Serialize, Deserialize)]
+struct WindowState {
+    width: f32,
+    height: f32,
+}
+
+fn window_state_path() -> PathBuf {
+    settings::config_dir().join("window.json")
+}
+
+fn load_window_size() -> [f32; 2] {
+    fs::read_to_string(window_state_path())
+        .ok()
+        .and_then(|data| serde_json::from_str::<WindowState>(&data).ok())
+        .map(|s| [s.width.max(MIN_SIZE[0]), s.height.max(MIN_SIZE[1])])
+        .unwrap_or(DEFAULT_SIZE)
+}
+
+fn save_window_size(size: egui::Vec2) {
+    let state = WindowState {
+        width: size.x,
+        height: size.y,
+    };
+    if let Ok(data) = serde_json::to_string(&state)
+        && let Err(e) = settings::write_atomic(&window_state_path(), data.as_bytes())
+    {
+        eprintln!("[settings] failed to save window size: {e}");
+    }
+}
+
+/// Runs the window in this process. `--edit-alias <file> <title>` opens it
+/// on the alias of that file, see [`open`].
 pub fn run() {
     let draft = Settings::load();
+    let alias: Vec<String> = std::env::args()
+        .skip_while(|a| a != "--edit-alias")
+        .skip(1)
+        .take(2)
+        .collect();
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
-            .with_inner_size([480.0, 580.0])
-            .with_resizable(false)
+            .with_inner_size(load_window_size())
+            .with_min_inner_size(MIN_SIZE)
+            .with_resizable(true)
             .with_title("Figma Rich Presence")
             .with_icon(load_icon()),
         ..Default::default()
@@ -73,14 +149,11 @@ pub fn run() {
         options,
         Box::new(move |cc| {
             configure_style(&cc.egui_ctx);
-            let custom_name_buf = match &draft.app_name {
-                AppName::Custom(s) => s.clone(),
-                _ => String::new(),
-            };
-            Ok(Box::new(SettingsWindow {
-                draft,
-                custom_name_buf,
-            }))
+            let mut window = SettingsWindow::new(draft, Preview::new(), None);
+            if let [file, title] = alias.as_slice() {
+                window.edit_alias(file, title);
+            }
+            Ok(Box::new(window))
         }),
     ) {
         eprintln!("[settings] window error: {e}");
@@ -88,113 +161,1106 @@ pub fn run() {
 }
 
 fn configure_style(ctx: &egui::Context) {
-    let mut style = (*ctx.style()).clone();
-    style.visuals.window_rounding = egui::Rounding::same(12.0);
-    style.visuals.widgets.noninteractive.rounding = egui::Rounding::same(8.0);
-    style.visuals.widgets.inactive.rounding = egui::Rounding::same(8.0);
-    style.visuals.widgets.hovered.rounding = egui::Rounding::same(8.0);
-    style.visuals.widgets.active.rounding = egui::Rounding::same(8.0);
-    style.spacing.item_spacing = egui::vec2(8.0, 6.0);
-    style.spacing.button_padding = egui::vec2(12.0, 6.0);
-    ctx.set_style(style);
+    ctx.all_styles_mut(|style| {
+        style.visuals.window_rounding = egui::Rounding::same(12.0);
+        style.visuals.widgets.noninteractive.rounding = egui::Rounding::same(8.0);
+        style.visuals.widgets.inactive.rounding = egui::Rounding::same(8.0);
+        style.visuals.widgets.hovered.rounding = egui::Rounding::same(8.0);
+        style.visuals.widgets.active.rounding = egui::Rounding::same(8.0);
+        // Make the keyboard focus easy to spot: focused widgets are drawn
+        // with the active style, focused text fields with the selection.
+        style.visuals.widgets.active.bg_stroke.width = 2.0;
+        style.visuals.selection.stroke.width = 2.0;
+        style.spacing.item_spacing = egui::vec2(8.0, 6.0);
+        style.spacing.button_padding = egui::vec2(12.0, 6.0);
+    });
+}
+
+fn apply_appearance(ctx: &egui::Context, appearance: &Appearance) {
+    ctx.set_theme(match appearance.theme {
+        Theme::System => egui::ThemePreference::System,
+        Theme::Light => egui::ThemePreference::Light,
+        Theme::Dark => egui::ThemePreference::Dark,
+    });
+    ctx.set_zoom_factor(appearance.ui_scale);
+}
+
+fn default_bundle_path() -> String {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("figma-rich-presence.json")
+        .display()
+        .to_string()
+}
+
+/// Disables the rest of `ui` when `key` is managed by the policy.
+fn lock_if_managed(ui: &mut egui::Ui, key: &str) {
+    if policy::is_locked(key) {
+        ui.disable();
+    }
+}
+
+/// Shows what kind of image `value` is, or why Discord wouldn't display it.
+fn image_feedback(ui: &mut egui::Ui, probes: &mut ImageProbes, value: &str, strings: &Strings) {
+    if value.is_empty() {
+        return;
+    }
+    let error = ui.visuals().error_fg_color;
+    match check_image(value) {
+        Err(e) => {
+            ui.colored_label(error, e.text(strings));
+        }
+        Ok(ImageKind::AssetKey) => {
+            ui.weak(strings.image_asset_key);
+        }
+        Ok(ImageKind::Url) => match probes.get(value) {
+            None => {
+                ui.weak(strings.image_url);
+            }
+            Some(ProbeState::Checking) => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.weak(strings.image_checking);
+                });
+            }
+            Some(ProbeState::Ok) => {
+                ui.weak(strings.image_reachable);
+            }
+            Some(ProbeState::Failed(e)) => {
+                ui.colored_label(error, e);
+            }
+        },
+    }
+}
+
+/// Names a widget that has no visible label of its own for screen readers.
+fn accessible_name(response: &egui::Response, typ: egui::WidgetType, name: &str) {
+    response.widget_info(|| egui::WidgetInfo::labeled(typ, response.enabled(), name));
+}
+
+fn time_edit(ui: &mut egui::Ui, time: &mut NaiveTime, name: &str, strings: &Strings) {
+    let mut hour = time.hour();
+    let mut minute = time.minute();
+    let hour_response = ui.add(
+        egui::DragValue::new(&mut hour)
+            .range(0..=23)
+            .custom_formatter(|n, _| format!("{n:02}")),
+    );
+    accessible_name(
+        &hour_response,
+        egui::WidgetType::DragValue,
+        &format!("{name} {}", strings.hour),
+    );
+    ui.label(":");
+    let minute_response = ui.add(
+        egui::DragValue::new(&mut minute)
+            .range(0..=59)
+            .custom_formatter(|n, _| format!("{n:02}")),
+    );
+    accessible_name(
+        &minute_response,
+        egui::WidgetType::DragValue,
+        &format!("{name} {}", strings.minute),
+    );
+    let hour_changed = hour_response.changed();
+    let minute_changed = minute_response.changed();
+    if (hour_changed || minute_changed)
+        && let Some(t) = NaiveTime::from_hms_opt(hour, minute, 0)
+    {
+        *time = t;
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Tab {
+    General,
+    Images,
+    Privacy,
+    Advanced,
+    About,
+}
+
+impl Tab {
+    const ALL: [Tab; 5] = [
+        Tab::General,
+        Tab::Images,
+        Tab::Privacy,
+        Tab::Advanced,
+        Tab::About,
+    ];
+
+    fn label(self, strings: &Strings) -> &'static str {
+        match self {
+            Tab::General => strings.tab_general,
+            Tab::Images => strings.tab_images,
+            Tab::Privacy => strings.tab_privacy,
+            Tab::Advanced => strings.tab_advanced,
+            Tab::About => strings.tab_about,
+        }
+    }
 }
 
 struct SettingsWindow {
+    notice: Option<Notice>,
+    save_error: Option<String>,
+    /// The settings as they are on disk, to tell whether the draft has
+    /// unsaved changes.
+    saved: Settings,
     draft: Settings,
+    tab: Tab,
+    confirm_close: bool,
+    /// Set once closing has been confirmed, so the close request that
+    /// follows is let through.
+    closing: bool,
+    window_size: Option<egui::Vec2>,
+    applied_appearance: Option<Appearance>,
+    /// Updated on save when the window shares the tray's settings.
+    shared: Option<Arc<RwLock<Settings>>>,
+    /// Tells the tray about a save, when hosted on its event loop.
+    proxy: Option<EventLoopProxy<TrayEvent>>,
     custom_name_buf: String,
+    processes_buf: String,
+    profile_name_buf: String,
+    bundle_path: String,
+    export_personal: bool,
+    import_preview: Option<(Settings, Vec<Change>)>,
+    bundle_status: Option<Result<String, String>>,
+    preview: Preview,
+    probes: ImageProbes,
+    /// The file whose alias field takes the focus when next shown.
+    focus_alias: Option<String>,
 }
 
-impl eframe::App for SettingsWindow {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        egui::CentralPanel::default().show(ctx, |ui| {
-            egui::ScrollArea::vertical().show(ui, |ui| {
-                ui.add_space(8.0);
-                ui.heading("Settings");
-                ui.add_space(8.0);
+enum ProfileAction {
+    Switch(String),
+    Create,
+    Duplicate,
+    Rename,
+    Delete,
+}
 
-                ui.strong("App Name");
-                ui.add_space(4.0);
+impl SettingsWindow {
+    fn new(draft: Settings, preview: Preview, shared: Option<Arc<RwLock<Settings>>>) -> Self {
+        let mut window = Self {
+            notice: settings::take_notice(),
+            save_error: None,
+            saved: draft.clone(),
+            draft,
+            tab: Tab::General,
+            confirm_close: false,
+            closing: false,
+            window_size: None,
+            applied_appearance: None,
+            shared,
+            proxy: None,
+            custom_name_buf: String::new(),
+            processes_buf: String::new(),
+            profile_name_buf: String::new(),
+            bundle_path: default_bundle_path(),
+            export_personal: false,
+            import_preview: None,
+            bundle_status: None,
+            preview,
+            probes: ImageProbes::new(),
+            focus_alias: None,
+        };
+        window.refresh_buffers();
+        window
+    }
+
+    fn refresh_buffers(&mut self) {
+        self.profile_name_buf = self.draft.active_profile.clone();
+        self.processes_buf = self.draft.privacy.processes.join("\n");
+        self.custom_name_buf = match &self.draft.app_name {
+            AppName::Custom(s) => s.clone(),
+            _ => String::new(),
+        };
+    }
+
+    fn share_section(&mut self, ui: &mut egui::Ui) {
+        let strings = self.draft.strings();
+        let heading = ui.strong(strings.import_export);
+        ui.add_space(4.0);
+        ui.add(
+            egui::TextEdit::singleline(&mut self.bundle_path)
+                .hint_text(strings.settings_file)
+                .desired_width(f32::INFINITY),
+        )
+        .labelled_by(heading.id);
+        ui.horizontal(|ui| {
+            if ui.button(strings.export).clicked() {
+                let path = PathBuf::from(self.bundle_path.trim());
+                self.bundle_status = Some(
+                    bundle::export_to_file(&self.draft, &path, self.export_personal)
+                        .map(|()| fill(strings.exported_to, &[&path.display()]))
+                        .map_err(|e| format!("{e:#}")),
+                );
+            }
+            if ui.button(strings.import).clicked() {
+                let path = PathBuf::from(self.bundle_path.trim());
+                let preview = bundle::read(&path)
+                    .and_then(|b| bundle::import(&self.draft, &b))
+                    .and_then(|imported| {
+                        let changes = bundle::diff(&self.draft, &imported)?;
+                        Ok((imported, changes))
+                    });
+                match preview {
+                    Ok(preview) => {
+                        self.bundle_status = None;
+                        self.import_preview = Some(preview);
+                    }
+                    Err(e) => self.bundle_status = Some(Err(format!("{e:#}"))),
+                }
+            }
+            ui.checkbox(&mut self.export_personal, strings.include_personal);
+        });
+
+        if let Some((imported, changes)) = &self.import_preview {
+            let mut apply = None;
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                if changes.is_empty() {
+                    ui.label(strings.import_no_changes);
+                } else {
+                    ui.label(fill(strings.import_changes, &[&changes.len()]));
+                    egui::ScrollArea::vertical()
+                        .max_height(120.0)
+                        .show(ui, |ui| {
+                            for change in changes {
+                                ui.label(format!(
+                                    "{}: {} → {}",
+                                    change.key, change.old, change.new
+                                ));
+                            }
+                        });
+                }
                 ui.horizontal(|ui| {
-                    let is_custom = matches!(self.draft.app_name, AppName::Custom(_));
                     if ui
-                        .radio(self.draft.app_name == AppName::Figma, "Figma")
+                        .add_enabled(!changes.is_empty(), egui::Button::new(strings.apply))
                         .clicked()
                     {
-                        self.draft.app_name = AppName::Figma;
+                        apply = Some(true);
                     }
-                    if ui
-                        .radio(
-                            self.draft.app_name == AppName::FigmaDesktop,
-                            "Figma Desktop",
-                        )
-                        .clicked()
-                    {
-                        self.draft.app_name = AppName::FigmaDesktop;
+                    if ui.button(strings.discard).clicked() {
+                        apply = Some(false);
                     }
-                    if ui.radio(is_custom, "Custom").clicked() {
-                        self.draft.app_name = AppName::Custom(self.custom_name_buf.clone());
+                });
+            });
+            match apply {
+                Some(true) => {
+                    self.draft = imported.clone();
+                    self.import_preview = None;
+                    self.refresh_buffers();
+                    self.bundle_status = Some(Ok(strings.imported.to_string()));
+                }
+                Some(false) => self.import_preview = None,
+                None => {}
+            }
+        }
+
+        match &self.bundle_status {
+            Some(Ok(msg)) => {
+                ui.label(msg);
+            }
+            Some(Err(err)) => {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+            None => {}
+        }
+    }
+
+    fn advanced_section(&mut self, ui: &mut egui::Ui) {
+        let strings = self.draft.strings();
+        let advanced = &mut self.draft.advanced;
+        ui.strong(strings.advanced);
+        ui.add_space(4.0);
+        lock_if_managed(ui, "advanced");
+        egui::Grid::new("advanced_grid")
+            .num_columns(2)
+            .spacing([8.0, 8.0])
+            .show(ui, |ui| {
+                for (label, value, range) in [
+                    (
+                        strings.figma_polling,
+                        &mut advanced.figma_polling_secs,
+                        FIGMA_POLLING_RANGE,
+                    ),
+                    (
+                        strings.discord_updates,
+                        &mut advanced.presence_update_secs,
+                        PRESENCE_UPDATE_RANGE,
+                    ),
+                    (
+                        strings.idle_after,
+                        &mut advanced.idle_threshold_secs,
+                        IDLE_THRESHOLD_RANGE,
+                    ),
+                ] {
+                    let label = ui.label(label);
+                    ui.add(egui::DragValue::new(value).range(range).suffix(" s"))
+                        .labelled_by(label.id);
+                    ui.end_row();
+                }
+            });
+        ui.checkbox(
+            &mut advanced.battery_saver,
+            fill(strings.battery_saver, &[&settings::BATTERY_SAVER_FACTOR]),
+        );
+    }
+
+    fn apply_profile_action(&mut self, action: ProfileAction) {
+        let strings = self.draft.strings();
+        let active = self.draft.active_profile.clone();
+        match action {
+            ProfileAction::Switch(name) => {
+                self.draft.switch_profile(&name);
+            }
+            ProfileAction::Create => {
+                self.draft.create_profile(strings.profile, false);
+            }
+            ProfileAction::Duplicate => {
+                self.draft
+                    .create_profile(&fill(strings.profile_copy, &[&active]), true);
+            }
+            ProfileAction::Rename => {
+                self.draft.rename_profile(&active, &self.profile_name_buf);
+            }
+            ProfileAction::Delete => {
+                self.draft.delete_profile(&active);
+            }
+        }
+        self.refresh_buffers();
+    }
+
+    fn profile_section(&mut self, ui: &mut egui::Ui) {
+        let strings = self.draft.strings();
+        let mut action = None;
+        ui.strong(strings.profile);
+        ui.add_space(4.0);
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("profile")
+                .selected_text(&self.draft.active_profile)
+                .show_ui(ui, |ui| {
+                    for name in self.draft.profile_names() {
+                        let selected = name == self.draft.active_profile;
+                        if ui.selectable_label(selected, &name).clicked() && !selected {
+                            action = Some(ProfileAction::Switch(name));
+                        }
                     }
-                    if ui
-                        .add_enabled(
-                            is_custom,
-                            egui::TextEdit::singleline(&mut self.custom_name_buf)
-                                .hint_text("App name")
-                                .desired_width(140.0),
+                });
+            if ui.button(strings.profile_new).clicked() {
+                action = Some(ProfileAction::Create);
+            }
+            if ui.button(strings.profile_duplicate).clicked() {
+                action = Some(ProfileAction::Duplicate);
+            }
+            if ui
+                .add_enabled(
+                    self.draft.profiles.len() > 1,
+                    egui::Button::new(strings.profile_delete),
+                )
+                .clicked()
+            {
+                action = Some(ProfileAction::Delete);
+            }
+        });
+        ui.horizontal(|ui| {
+            let name_edit = ui.add(
+                egui::TextEdit::singleline(&mut self.profile_name_buf)
+                    .hint_text(strings.profile_name)
+                    .desired_width(180.0),
+            );
+            accessible_name(&name_edit, egui::WidgetType::TextEdit, strings.profile_name);
+            let name = self.profile_name_buf.trim();
+            let can_rename = !name.is_empty()
+                && name != self.draft.active_profile
+                && !self.draft.profiles.iter().any(|p| p.name == name);
+            if ui
+                .add_enabled(can_rename, egui::Button::new(strings.profile_rename))
+                .clicked()
+            {
+                action = Some(ProfileAction::Rename);
+            }
+        });
+        if let Some(action) = action {
+            self.apply_profile_action(action);
+        }
+    }
+}
+
+impl SettingsWindow {
+    /// Rules that change nothing, such as one added by [`Self::edit_alias`]
+    /// and left blank, don't count as changes.
+    fn is_dirty(&self) -> bool {
+        let (mut draft, mut saved) = (self.draft.clone(), self.saved.clone());
+        draft.tidy_file_rules();
+        saved.tidy_file_rules();
+        draft != saved
+    }
+
+    /// Saves only what was edited here, on top of the settings as they are
+    /// now, so changes the tray or `config set` made meanwhile are kept. The
+    /// tray saves each change it makes, so the file has all of them; its
+    /// copy stays locked until the merged settings replace it.
+    fn save(&mut self) -> bool {
+        self.draft.tidy_file_rules();
+        let (saved, draft) = (&self.saved, &self.draft);
+        let mut shared = self.shared.as_ref().map(|shared| shared.write().unwrap());
+        let result = draft.validate().and_then(|()| {
+            settings::update(|current| {
+                current.apply_changes(saved, draft)?;
+                current.validate()
+            })
+        });
+        match result {
+            Ok(updated) => {
+                if let Some(shared) = &mut shared {
+                    **shared = updated.clone();
+                }
+                drop(shared);
+                self.saved = updated.clone();
+                self.draft = updated;
+                self.refresh_buffers();
+                self.save_error = None;
+                if let Some(proxy) = &self.proxy {
+                    tray::notify(proxy);
+                }
+                true
+            }
+            Err(e) => {
+                eprintln!("[settings] failed to save: {e:#}");
+                self.save_error =
+                    Some(fill(self.draft.strings().save_failed, &[&format!("{e:#}")]));
+                false
+            }
+        }
+    }
+
+    /// Starts reverting the draft to defaults. Saved profiles, schedule rules
+    /// and file rules are kept, so only the active profile's values and the
+    /// general settings are reset. Managed fields keep their policy values
+    /// and nothing is written until Save.
+    fn reset_to_defaults(&mut self) {
+        let mut defaults = Settings::default();
+        let draft = &mut self.draft;
+        defaults.active_profile = std::mem::take(&mut draft.active_profile);
+        defaults.profiles = std::mem::take(&mut draft.profiles);
+        defaults.schedule = std::mem::take(&mut draft.schedule);
+        defaults.privacy.files = std::mem::take(&mut draft.privacy.files);
+        defaults.launch_at_login = draft.launch_at_login;
+        defaults.normalize();
+        self.draft = defaults;
+        self.import_preview = None;
+        self.refresh_buffers();
+    }
+
+    /// Switches to the Privacy tab with the alias of `file` focused, adding
+    /// a rule for it to the draft as needed. Nothing is saved until an alias
+    /// is entered and saved.
+    fn edit_alias(&mut self, file: &str, title: &str) {
+        let files = &mut self.draft.privacy.files;
+        if !files.iter().any(|rule| rule.file == file) {
+            files.push(FileRule {
+                file: file.to_string(),
+                title: title.to_string(),
+                ..FileRule::default()
+            });
+        }
+        self.tab = Tab::Privacy;
+        self.focus_alias = Some(file.to_string());
+    }
+
+    /// Asks before closing when the draft has unsaved changes. The host
+    /// closes the window once `closing` is set.
+    fn request_close(&mut self) {
+        if self.is_dirty() {
+            self.confirm_close = true;
+        } else {
+            self.closing = true;
+        }
+    }
+
+    fn general_tab(&mut self, ui: &mut egui::Ui) {
+        self.preview.show(ui, &self.draft);
+        ui.add_space(8.0);
+        ui.separator();
+
+        self.profile_section(ui);
+        ui.add_space(8.0);
+        ui.separator();
+
+        let strings = self.draft.strings();
+        ui.strong(strings.app_name);
+        ui.add_space(4.0);
+        ui.horizontal(|ui| {
+            lock_if_managed(ui, "app_name");
+            let is_custom = matches!(self.draft.app_name, AppName::Custom(_));
+            if ui
+                .radio(self.draft.app_name == AppName::Figma, "Figma")
+                .clicked()
+            {
+                self.draft.app_name = AppName::Figma;
+            }
+            if ui
+                .radio(
+                    self.draft.app_name == AppName::FigmaDesktop,
+                    "Figma Desktop",
+                )
+                .clicked()
+            {
+                self.draft.app_name = AppName::FigmaDesktop;
+            }
+            let custom = ui.radio(is_custom, strings.app_name_custom);
+            if custom.clicked() {
+                self.draft.app_name = AppName::Custom(self.custom_name_buf.clone());
+            }
+            if ui
+                .add_enabled(
+                    is_custom,
+                    egui::TextEdit::singleline(&mut self.custom_name_buf)
+                        .hint_text(strings.app_name_hint)
+                        .desired_width(140.0),
+                )
+                .labelled_by(custom.id)
+                .changed()
+                && is_custom
+            {
+                self.draft.app_name = AppName::Custom(self.custom_name_buf.clone());
+            }
+        });
+        ui.add_space(8.0);
+        ui.separator();
+        ui.strong(strings.other_settings);
+        ui.add_space(4.0);
+        ui.add_enabled(
+            !policy::is_locked("hide_filename"),
+            egui::Checkbox::new(&mut self.draft.hide_filename, strings.hide_file_names),
+        );
+        ui.add_enabled(
+            !policy::is_locked("disable_idle"),
+            egui::Checkbox::new(&mut self.draft.disable_idle, strings.disable_idle),
+        );
+        ui.add_enabled(
+            !policy::is_locked("launch_at_login"),
+            egui::Checkbox::new(&mut self.draft.launch_at_login, strings.start_on_login),
+        );
+        ui.add_space(8.0);
+        ui.separator();
+        self.appearance_section(ui);
+    }
+
+    fn appearance_section(&mut self, ui: &mut egui::Ui) {
+        let strings = self.draft.strings();
+        let language = &mut self.draft.language;
+        let appearance = &mut self.draft.appearance;
+        let appearance_locked = policy::is_locked("appearance");
+        ui.strong(strings.appearance);
+        ui.add_space(4.0);
+        egui::Grid::new("appearance_grid")
+            .num_columns(2)
+            .spacing([8.0, 8.0])
+            .show(ui, |ui| {
+                let label = ui.label(strings.language);
+                let system = fill(
+                    strings.language_system,
+                    &[&Language::system().native_name()],
+                );
+                ui.add_enabled_ui(!policy::is_locked("language"), |ui| {
+                    egui::ComboBox::from_id_salt("language")
+                        .selected_text(match language {
+                            Some(language) => language.native_name().to_string(),
+                            None => system.clone(),
+                        })
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(language, None, system);
+                            for option in Language::ALL {
+                                ui.selectable_value(language, Some(option), option.native_name());
+                            }
+                        })
+                        .response
+                        .labelled_by(label.id);
+                });
+                ui.end_row();
+
+                let label = ui.label(strings.theme);
+                ui.add_enabled_ui(!appearance_locked, |ui| {
+                    egui::ComboBox::from_id_salt("theme")
+                        .selected_text(appearance.theme.label(strings))
+                        .show_ui(ui, |ui| {
+                            for theme in Theme::ALL {
+                                ui.selectable_value(
+                                    &mut appearance.theme,
+                                    theme,
+                                    theme.label(strings),
+                                );
+                            }
+                        })
+                        .response
+                        .labelled_by(label.id);
+                });
+                ui.end_row();
+
+                let label = ui.label(strings.interface_size);
+                ui.add_enabled(
+                    !appearance_locked,
+                    egui::Slider::new(&mut appearance.ui_scale, UI_SCALE_RANGE)
+                        .step_by(0.05)
+                        .custom_formatter(|v, _| format!("{:.0}%", v * 100.0))
+                        .custom_parser(|s| {
+                            s.trim()
+                                .trim_end_matches('%')
+                                .parse::<f64>()
+                                .ok()
+                                .map(|v| v / 100.0)
+                        }),
+                )
+                .labelled_by(label.id);
+                ui.end_row();
+            });
+        ui.weak(strings.shortcuts_hint);
+    }
+
+    fn images_tab(&mut self, ui: &mut egui::Ui) {
+        let strings = self.draft.strings();
+        ui.strong(strings.activity_images);
+        ui.add_space(4.0);
+        let label = ui.label(strings.default_image);
+        ui.add_enabled(
+            !policy::is_locked("default_image"),
+            egui::TextEdit::singleline(&mut self.draft.default_image)
+                .hint_text(strings.default_image_hint)
+                .desired_width(f32::INFINITY),
+        )
+        .labelled_by(label.id);
+        image_feedback(ui, &mut self.probes, &self.draft.default_image, strings);
+        ui.add_space(4.0);
+
+        let probes = &mut self.probes;
+        egui::Grid::new("overrides_grid")
+            .num_columns(2)
+            .spacing([8.0, 8.0])
+            .show(ui, |ui| {
+                for &key in STATE_KEYS {
+                    let entry = self
+                        .draft
+                        .image_overrides
+                        .entry(key.to_string())
+                        .or_default();
+                    let locked = policy::is_locked(&format!("image_overrides.{key}"));
+                    let checkbox = ui.add_enabled(
+                        !locked,
+                        egui::Checkbox::new(&mut entry.enabled, strings.state(key)),
+                    );
+                    ui.add_enabled(
+                        entry.enabled && !locked,
+                        egui::TextEdit::singleline(&mut entry.image_url)
+                            .hint_text(strings.image_hint)
+                            .desired_width(f32::INFINITY),
+                    )
+                    .labelled_by(checkbox.id);
+                    ui.end_row();
+                    if entry.enabled && !entry.image_url.is_empty() {
+                        ui.label("");
+                        image_feedback(ui, probes, &entry.image_url, strings);
+                        ui.end_row();
+                    }
+                }
+            });
+
+        ui.add_space(4.0);
+        let urls: Vec<String> = std::iter::once(&self.draft.default_image)
+            .chain(
+                self.draft
+                    .image_overrides
+                    .values()
+                    .filter(|entry| entry.enabled)
+                    .map(|entry| &entry.image_url),
+            )
+            .filter(|value| check_image(value) == Ok(ImageKind::Url))
+            .cloned()
+            .collect();
+        if ui
+            .add_enabled(!urls.is_empty(), egui::Button::new(strings.check_images))
+            .on_hover_text(strings.check_images_hover)
+            .clicked()
+        {
+            for url in &urls {
+                self.probes.start(ui.ctx(), url);
+            }
+        }
+    }
+
+    fn privacy_tab(&mut self, ui: &mut egui::Ui) {
+        let strings = self.draft.strings();
+        ui.strong(strings.privacy_mode);
+        ui.add_space(4.0);
+        ui.add_enabled(
+            !policy::is_locked("privacy.enabled"),
+            egui::Checkbox::new(&mut self.draft.privacy.enabled, strings.privacy_enable),
+        );
+        ui.add_enabled_ui(self.draft.privacy.enabled, |ui| {
+            ui.add_enabled_ui(!policy::is_locked("privacy.action"), |ui| {
+                ui.horizontal(|ui| {
+                    ui.radio_value(
+                        &mut self.draft.privacy.action,
+                        PrivacyAction::HideFilename,
+                        strings.hide_file_names,
+                    );
+                    ui.radio_value(
+                        &mut self.draft.privacy.action,
+                        PrivacyAction::ClearPresence,
+                        strings.privacy_clear,
+                    );
+                });
+            });
+            let label = ui.label(strings.privacy_processes);
+            if ui
+                .add_enabled(
+                    !policy::is_locked("privacy.processes"),
+                    egui::TextEdit::multiline(&mut self.processes_buf)
+                        .desired_rows(4)
+                        .desired_width(f32::INFINITY),
+                )
+                .labelled_by(label.id)
+                .changed()
+            {
+                self.draft.privacy.processes = self
+                    .processes_buf
+                    .lines()
+                    .map(str::trim)
+                    .filter(|l| !l.is_empty())
+                    .map(str::to_string)
+                    .collect();
+            }
+        });
+        ui.add_space(8.0);
+        ui.separator();
+        ui.scope(|ui| self.files_section(ui));
+        ui.add_space(8.0);
+        ui.separator();
+        ui.strong(strings.schedule);
+        ui.add_space(4.0);
+        let mut remove = None;
+        let schedule_locked = policy::is_locked("schedule");
+        for (i, rule) in self.draft.schedule.iter_mut().enumerate() {
+            ui.push_id(i, |ui| {
+                lock_if_managed(ui, "schedule");
+                ui.horizontal(|ui| {
+                    let enabled = ui.checkbox(&mut rule.enabled, "");
+                    accessible_name(
+                        &enabled,
+                        egui::WidgetType::Checkbox,
+                        strings.schedule_rule_enabled,
+                    );
+                    egui::ComboBox::from_id_salt("schedule_action")
+                        .selected_text(rule.action.label(strings))
+                        .show_ui(ui, |ui| {
+                            for action in ScheduleAction::ALL {
+                                ui.selectable_value(
+                                    &mut rule.action,
+                                    action,
+                                    action.label(strings),
+                                );
+                            }
+                        });
+                    time_edit(ui, &mut rule.start, strings.schedule_start, strings);
+                    ui.label(strings.schedule_to);
+                    time_edit(ui, &mut rule.end, strings.schedule_end, strings);
+                    if ui.small_button(strings.remove).clicked() {
+                        remove = Some(i);
+                    }
+                });
+                ui.horizontal(|ui| {
+                    for day in WEEKDAYS {
+                        let mut on = rule.days.contains(&day);
+                        if ui.toggle_value(&mut on, strings.weekday(day)).changed() {
+                            if on {
+                                rule.days.push(day);
+                                rule.days.sort_by_key(|d| d.num_days_from_monday());
+                            } else {
+                                rule.days.retain(|d| *d != day);
+                            }
+                        }
+                    }
+                });
+            });
+            ui.add_space(4.0);
+        }
+        if let Some(i) = remove {
+            self.draft.schedule.remove(i);
+        }
+        if ui
+            .add_enabled(!schedule_locked, egui::Button::new(strings.add_rule))
+            .clicked()
+        {
+            self.draft.schedule.push(ScheduleRule::default());
+        }
+    }
+
+    fn files_section(&mut self, ui: &mut egui::Ui) {
+        let strings = self.draft.strings();
+        ui.strong(strings.files);
+        ui.add_space(4.0);
+        let files = &mut self.draft.privacy.files;
+        if files.is_empty() {
+            ui.weak(strings.files_hint);
+            return;
+        }
+        lock_if_managed(ui, "privacy.files");
+        let focus = self.focus_alias.take();
+        let mut remove = None;
+        for (i, rule) in files.iter_mut().enumerate() {
+            ui.push_id(i, |ui| {
+                let title = if rule.title.is_empty() {
+                    &rule.file
+                } else {
+                    &rule.title
+                };
+                let label = ui.label(title.as_str());
+                ui.horizontal(|ui| {
+                    let alias = ui
+                        .add(
+                            egui::TextEdit::singleline(&mut rule.alias)
+                                .hint_text(strings.file_alias)
+                                .char_limit(MAX_ALIAS_LEN)
+                                .desired_width(200.0),
                         )
-                        .changed()
-                        && is_custom
-                    {
-                        self.draft.app_name = AppName::Custom(self.custom_name_buf.clone());
+                        .labelled_by(label.id);
+                    if focus.as_deref() == Some(rule.file.as_str()) {
+                        alias.request_focus();
+                        alias.scroll_to_me(None);
+                    }
+                    if ui.small_button(strings.remove).clicked() {
+                        remove = Some(i);
                     }
                 });
-                ui.add_space(8.0);
-                ui.separator();
-                ui.strong("Other Settings");
-                ui.add_space(4.0);
-                ui.checkbox(&mut self.draft.hide_filename, "Hide File Names");
-                ui.checkbox(&mut self.draft.disable_idle, "Disable Idle Detection");
-                ui.separator();
-                ui.strong("Activity Images");
-                ui.add_space(4.0);
-                ui.label("Default Image URL");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut rule.hide_name, strings.file_hide_name);
+                    ui.checkbox(&mut rule.exclude, strings.file_exclude);
+                });
+            });
+            ui.add_space(4.0);
+        }
+        if let Some(i) = remove {
+            files.remove(i);
+        }
+    }
+
+    fn notifications_section(&mut self, ui: &mut egui::Ui) {
+        let strings = self.draft.strings();
+        let notifications = &mut self.draft.notifications;
+        ui.strong(strings.notifications);
+        ui.add_space(4.0);
+        lock_if_managed(ui, "notifications");
+        ui.checkbox(&mut notifications.enabled, strings.notify_enabled);
+        ui.add_enabled_ui(notifications.enabled, |ui| {
+            ui.checkbox(&mut notifications.discord, strings.notify_discord);
+            ui.checkbox(&mut notifications.figma, strings.notify_figma);
+            ui.checkbox(&mut notifications.settings, strings.notify_settings);
+            ui.checkbox(&mut notifications.updates, strings.notify_updates);
+            ui.horizontal(|ui| {
+                let label = ui.label(strings.notify_interval);
                 ui.add(
-                    egui::TextEdit::singleline(&mut self.draft.default_image)
-                        .hint_text("Default Figma Icon"),
-                );
-                ui.add_space(4.0);
+                    egui::DragValue::new(&mut notifications.min_interval_secs)
+                        .range(settings::NOTIFICATION_INTERVAL_RANGE)
+                        .suffix(" s"),
+                )
+                .labelled_by(label.id);
+            });
+        });
+    }
 
-                egui::Grid::new("overrides_grid")
-                    .num_columns(2)
-                    .spacing([8.0, 8.0])
-                    .show(ui, |ui| {
-                        for &(key, display) in STATE_ENTRIES {
-                            let entry = self
-                                .draft
-                                .image_overrides
-                                .entry(key.to_string())
-                                .or_default();
-                            ui.checkbox(&mut entry.enabled, display);
-                            ui.add_enabled(
-                                entry.enabled,
-                                egui::TextEdit::singleline(&mut entry.image_url)
-                                    .hint_text("Image URL")
-                                    .desired_width(f32::INFINITY),
-                            );
-                            ui.end_row();
-                        }
-                    });
+    fn advanced_tab(&mut self, ui: &mut egui::Ui) {
+        ui.scope(|ui| self.advanced_section(ui));
+        ui.add_space(8.0);
+        ui.separator();
+        ui.scope(|ui| self.notifications_section(ui));
+        ui.add_space(8.0);
+        ui.separator();
+        self.share_section(ui);
+    }
+
+    fn about_tab(&mut self, ui: &mut egui::Ui) {
+        let strings = self.draft.strings();
+        ui.strong("Figma Rich Presence");
+        ui.label(fill(strings.version, &[&env!("CARGO_PKG_VERSION")]));
+        ui.add_space(8.0);
+        ui.label(strings.settings_folder);
+        let dir = settings::config_dir();
+        ui.horizontal(|ui| {
+            ui.monospace(dir.display().to_string());
+            if ui.small_button(strings.copy).clicked() {
+                ui.ctx().copy_text(dir.display().to_string());
+            }
+        });
+        if let Some(policy) = policy::get() {
+            ui.add_space(8.0);
+            ui.label(fill(
+                strings.managed_by,
+                &[
+                    &policy.as_object().map_or(0, |m| m.len()),
+                    &policy::path().display(),
+                ],
+            ));
+        }
+    }
 
+    fn confirm_close_dialog(&mut self, ctx: &egui::Context) {
+        let strings = self.draft.strings();
+        egui::Window::new(strings.unsaved_title)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(strings.unsaved_prompt);
                 ui.add_space(8.0);
-                ui.separator();
-                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    if ui.button(strings.save).clicked() {
+                        self.confirm_close = false;
+                        self.closing = self.save();
+                    }
+                    if ui.button(strings.discard).clicked() {
+                        self.confirm_close = false;
+                        self.closing = true;
+                    }
+                    if ui.button(strings.keep_editing).clicked() {
+                        self.confirm_close = false;
+                    }
+                });
+            });
+    }
+
+    /// Applies the draft's theme and scale, and takes zooming with Ctrl+/-
+    /// into the draft so both stay in step.
+    fn sync_appearance(&mut self, ctx: &egui::Context) {
+        if self.applied_appearance.as_ref() == Some(&self.draft.appearance) {
+            let zoom = ctx.zoom_factor();
+            if zoom == self.draft.appearance.ui_scale {
+                return;
+            }
+            self.draft.appearance.ui_scale =
+                zoom.clamp(*UI_SCALE_RANGE.start(), *UI_SCALE_RANGE.end());
+        }
+        apply_appearance(ctx, &self.draft.appearance);
+        self.applied_appearance = Some(self.draft.appearance.clone());
+    }
+
+    fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        use egui::{Key, Modifiers};
+        // Escape belongs to the focused field or open popup first.
+        let escape_free = ctx.memory(|m| m.focused().is_none() && !m.any_popup_open());
+        let (save, escape, next_tab, prev_tab) = ctx.input_mut(|i| {
+            (
+                i.consume_key(Modifiers::COMMAND, Key::S),
+                escape_free && i.consume_key(Modifiers::NONE, Key::Escape),
+                i.consume_key(Modifiers::COMMAND, Key::PageDown),
+                i.consume_key(Modifiers::COMMAND, Key::PageUp),
+            )
+        });
+        if escape {
+            if self.confirm_close {
+                self.confirm_close = false;
+            } else {
+                self.request_close();
+            }
+        }
+        if save && self.save() {
+            self.closing = true;
+        }
+        let current = Tab::ALL.iter().position(|t| *t == self.tab).unwrap_or(0);
+        if next_tab {
+            self.tab = Tab::ALL[(current + 1) % Tab::ALL.len()];
+        }
+        if prev_tab {
+            self.tab = Tab::ALL[(current + Tab::ALL.len() - 1) % Tab::ALL.len()];
+        }
+    }
+
+    fn show(&mut self, ctx: &egui::Context) {
+        self.sync_appearance(ctx);
+        self.handle_shortcuts(ctx);
+        let strings = self.draft.strings();
+        egui::TopBottomPanel::top("tabs").show(ctx, |ui| {
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                for tab in Tab::ALL {
+                    ui.selectable_value(&mut self.tab, tab, tab.label(strings));
+                }
+            });
+            ui.add_space(4.0);
+        });
 
-                ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
-                    if ui.button("Save").clicked() {
-                        self.draft.save();
-                        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        egui::TopBottomPanel::bottom("actions").show(ctx, |ui| {
+            ui.add_space(6.0);
+            if let Some(err) = &self.save_error {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+                ui.add_space(4.0);
+            }
+            ui.horizontal(|ui| {
+                if ui.button(strings.reset_defaults).clicked() {
+                    self.reset_to_defaults();
+                }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button(strings.save).clicked() && self.save() {
+                        self.closing = true;
+                    }
+                    if ui.button(strings.cancel).clicked() {
+                        self.request_close();
+                    }
+                    if self.is_dirty() {
+                        ui.weak(strings.unsaved_changes);
                     }
                 });
             });
+            ui.add_space(6.0);
+        });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.add_space(4.0);
+                if let Some(notice) = &self.notice {
+                    ui.colored_label(ui.visuals().warn_fg_color, notice.text(strings));
+                    ui.add_space(8.0);
+                }
+                if policy::get().is_some() && self.tab != Tab::About {
+                    ui.label(strings.managed_notice);
+                    ui.add_space(8.0);
+                }
+
+                match self.tab {
+                    Tab::General => self.general_tab(ui),
+                    Tab::Images => self.images_tab(ui),
+                    Tab::Privacy => self.privacy_tab(ui),
+                    Tab::Advanced => self.advanced_tab(ui),
+                    Tab::About => self.about_tab(ui),
+                }
+                ui.add_space(8.0);
+            });
         });
+
+        if self.confirm_close {
+            self.confirm_close_dialog(ctx);
+        }
+    }
+}
+
+impl eframe::App for SettingsWindow {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        if let Some(rect) = ctx.input(|i| i.viewport().inner_rect) {
+            self.window_size = Some(rect.size());
+        }
+        if ctx.input(|i| i.viewport().close_requested()) && !self.closing {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.request_close();
+        }
+        self.show(ctx);
+        if self.closing {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        }
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        if let Some(size) = self.window_size {
+            save_window_size(size);
+        }
     }
 }
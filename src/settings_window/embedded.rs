@@ -0,0 +1,303 @@
+use super::{MIN_SIZE, SettingsWindow, configure_style, load_window_size, save_window_size};
+use crate::figma::FigmaState;
+use crate::log_error;
+use crate::preview::Preview;
+use crate::settings::Settings;
+use crate::tray::TrayEvent;
+use anyhow::{Context, Result, anyhow};
+use eframe::egui;
+use egui_glow::{EguiGlow, egui_winit::accesskit_winit};
+use glutin::{
+    config::{Config, ConfigTemplateBuilder},
+    context::{ContextApi, ContextAttributesBuilder, NotCurrentGlContext, PossiblyCurrentContext},
+    display::{Display, DisplayApiPreference, GetGlDisplay, GlDisplay},
+    surface::{GlSurface, Surface, SurfaceAttributesBuilder, SwapInterval, WindowSurface},
+};
+use std::{
+    ffi::CString,
+    num::NonZeroU32,
+    sync::{Arc, Mutex, RwLock},
+    time::Instant,
+};
+use winit::{
+    dpi::{LogicalSize, PhysicalSize},
+    event::WindowEvent,
+    event_loop::{ActiveEventLoop, EventLoopProxy},
+    raw_window_handle::{HasDisplayHandle, HasWindowHandle},
+    window::{Icon, Window, WindowAttributes, WindowId},
+};
+
+/// The settings window hosted on the tray's event loop, sharing its state
+/// directly instead of going through the settings file.
+pub struct EmbeddedWindow {
+    window: Window,
+    gl_context: PossiblyCurrentContext,
+    gl_surface: Surface<WindowSurface>,
+    egui_glow: EguiGlow,
+    app: SettingsWindow,
+    repaint_at: Arc<Mutex<Option<Instant>>>,
+}
+
+fn window_icon() -> Option<Icon> {
+    let icon = super::load_icon();
+    Icon::from_rgba(icon.rgba, icon.width, icon.height).ok()
+}
+
+/// Does what `glutin_winit::DisplayBuilder` does, minus its picker that has
+/// to return a config even when the display offers none. The window is only
+/// created up front on Windows, where WGL needs it to offer modern OpenGL.
+fn pick_config(
+    event_loop: &ActiveEventLoop,
+    attributes: &WindowAttributes,
+    template: ConfigTemplateBuilder,
+) -> Result<(Option<Window>, Config)> {
+    #[cfg(target_os = "windows")]
+    let window = Some(
+        event_loop
+            .create_window(attributes.clone())
+            .context("failed to create window")?,
+    );
+    #[cfg(not(target_os = "windows"))]
+    let window: Option<Window> = {
+        let _ = attributes;
+        None
+    };
+    let raw_window = match &window {
+        Some(window) => Some(window.window_handle()?.as_raw()),
+        None => None,
+    };
+
+    #[cfg(target_os = "windows")]
+    let preference = DisplayApiPreference::WglThenEgl(raw_window);
+    #[cfg(target_os = "macos")]
+    let preference = DisplayApiPreference::Cgl;
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let preference =
+        DisplayApiPreference::GlxThenEgl(Box::new(winit::platform::x11::register_xlib_error_hook));
+
+    let display = unsafe { Display::new(event_loop.display_handle()?.as_raw(), preference) }
+        .map_err(|e| anyhow!("failed to open an OpenGL display: {e}"))?;
+    let template = match raw_window {
+        Some(handle) => template.compatible_with_native_window(handle),
+        None => template,
+    };
+    let config = unsafe { display.find_configs(template.build()) }
+        .map_err(|e| anyhow!("failed to pick an OpenGL config: {e}"))?
+        .next()
+        .ok_or_else(|| anyhow!("no OpenGL configs offered"))?;
+    Ok((window, config))
+}
+
+impl EmbeddedWindow {
+    /// Fails when no usable OpenGL context can be created, in which case the
+    /// caller falls back to the separate settings process.
+    pub fn open(
+        event_loop: &ActiveEventLoop,
+        settings: Arc<RwLock<Settings>>,
+        figma_state: Arc<RwLock<FigmaState>>,
+        privacy_active: Arc<RwLock<Option<String>>>,
+        proxy: EventLoopProxy<TrayEvent>,
+    ) -> Result<Self> {
+        let [width, height] = load_window_size();
+        let attributes = WindowAttributes::default()
+            .with_title("Figma Rich Presence")
+            .with_inner_size(LogicalSize::new(width, height))
+            .with_min_inner_size(LogicalSize::new(MIN_SIZE[0], MIN_SIZE[1]))
+            .with_resizable(true)
+            .with_window_icon(window_icon())
+            .with_visible(false);
+
+        let template = ConfigTemplateBuilder::new()
+            .prefer_hardware_accelerated(None)
+            .with_depth_size(0)
+            .with_stencil_size(0)
+            .with_transparency(false);
+        let (window, gl_config) = pick_config(event_loop, &attributes, template)?;
+        let gl_display = gl_config.display();
+
+        let window = match window {
+            Some(window) => window,
+            None => glutin_winit::finalize_window(event_loop, attributes, &gl_config)
+                .context("failed to create window")?,
+        };
+        let raw_handle = window.window_handle()?.as_raw();
+
+        let context = unsafe {
+            gl_display
+                .create_context(
+                    &gl_config,
+                    &ContextAttributesBuilder::new().build(Some(raw_handle)),
+                )
+                .or_else(|_| {
+                    gl_display.create_context(
+                        &gl_config,
+                        &ContextAttributesBuilder::new()
+                            .with_context_api(ContextApi::Gles(None))
+                            .build(Some(raw_handle)),
+                    )
+                })
+                .context("failed to create OpenGL context")?
+        };
+
+        let size = window.inner_size();
+        let surface_attributes = SurfaceAttributesBuilder::<WindowSurface>::new().build(
+            raw_handle,
+            NonZeroU32::new(size.width).unwrap_or(NonZeroU32::MIN),
+            NonZeroU32::new(size.height).unwrap_or(NonZeroU32::MIN),
+        );
+        let gl_surface = unsafe {
+            gl_display
+                .create_window_surface(&gl_config, &surface_attributes)
+                .context("failed to create OpenGL surface")?
+        };
+        let gl_context = context
+            .make_current(&gl_surface)
+            .context("failed to activate OpenGL context")?;
+        let _ = gl_surface.set_swap_interval(&gl_context, SwapInterval::Wait(NonZeroU32::MIN));
+
+        let gl = unsafe {
+            glow::Context::from_loader_function(|name| {
+                let name = CString::new(name).expect("GL function name contains a NUL byte");
+                gl_display.get_proc_address(&name)
+            })
+        };
+        let mut egui_glow = EguiGlow::new(event_loop, Arc::new(gl), None, None, true);
+        configure_style(&egui_glow.egui_ctx);
+        // The adapter has to exist before the window is first shown.
+        egui_glow.egui_winit.init_accesskit(&window, proxy.clone());
+
+        let repaint_at = Arc::new(Mutex::new(Some(Instant::now())));
+        let requested = Arc::clone(&repaint_at);
+        egui_glow
+            .egui_ctx
+            .set_request_repaint_callback(move |info| {
+                let at = Instant::now() + info.delay;
+                let mut requested = requested.lock().unwrap();
+                if requested.is_none_or(|current| at < current) {
+                    *requested = Some(at);
+                }
+            });
+
+        let draft = settings.read().unwrap().clone();
+        let mut app = SettingsWindow::new(
+            draft,
+            Preview::with_shared_state(figma_state, privacy_active),
+            Some(settings),
+        );
+        app.proxy = Some(proxy);
+
+        window.set_visible(true);
+        Ok(Self {
+            window,
+            gl_context,
+            gl_surface,
+            egui_glow,
+            app,
+            repaint_at,
+        })
+    }
+
+    pub fn id(&self) -> WindowId {
+        self.window.id()
+    }
+
+    pub fn focus(&self) {
+        self.window.set_minimized(false);
+        self.window.focus_window();
+    }
+
+    /// Switches to the Privacy tab to enter an alias for `file`, which the
+    /// tray's menu can't take.
+    pub fn edit_alias(&mut self, file: &str, title: &str) {
+        self.app.edit_alias(file, title);
+        self.window.request_redraw();
+    }
+
+    /// Requests a redraw when egui asked for one by now, and returns when it
+    /// next wants to repaint.
+    pub fn poll_repaint(&self) -> Option<Instant> {
+        let mut repaint_at = self.repaint_at.lock().unwrap();
+        match *repaint_at {
+            Some(at) if at <= Instant::now() => {
+                *repaint_at = None;
+                self.window.request_redraw();
+                None
+            }
+            other => other,
+        }
+    }
+
+    pub fn on_accesskit_event(&mut self, event: &accesskit_winit::WindowEvent) {
+        match event {
+            accesskit_winit::WindowEvent::InitialTreeRequested => {
+                self.egui_glow.egui_ctx.enable_accesskit();
+                self.window.request_redraw();
+            }
+            accesskit_winit::WindowEvent::ActionRequested(request) => {
+                self.egui_glow
+                    .egui_winit
+                    .on_accesskit_action_request(request.clone());
+                self.window.request_redraw();
+            }
+            accesskit_winit::WindowEvent::AccessibilityDeactivated => {
+                self.egui_glow.egui_ctx.disable_accesskit();
+            }
+        }
+    }
+
+    /// Returns true once the window has been closed.
+    pub fn on_window_event(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::CloseRequested => {
+                self.app.request_close();
+                self.window.request_redraw();
+            }
+            WindowEvent::RedrawRequested => {
+                self.redraw();
+                return self.app.closing;
+            }
+            WindowEvent::Resized(size) => self.resize(*size),
+            _ => {}
+        }
+
+        if self.egui_glow.on_window_event(&self.window, event).repaint {
+            self.window.request_redraw();
+        }
+        false
+    }
+
+    fn resize(&mut self, size: PhysicalSize<u32>) {
+        if let (Some(width), Some(height)) =
+            (NonZeroU32::new(size.width), NonZeroU32::new(size.height))
+        {
+            self.gl_surface.resize(&self.gl_context, width, height);
+        }
+        let logical = size.to_logical::<f32>(self.window.scale_factor());
+        self.app.window_size = Some(egui::vec2(logical.width, logical.height));
+    }
+
+    fn redraw(&mut self) {
+        let app = &mut self.app;
+        self.egui_glow.run(&self.window, |ctx| app.show(ctx));
+
+        unsafe {
+            use glow::HasContext;
+            let gl = self.egui_glow.painter.gl();
+            gl.clear_color(0.0, 0.0, 0.0, 1.0);
+            gl.clear(glow::COLOR_BUFFER_BIT);
+        }
+        self.egui_glow.paint(&self.window);
+        if let Err(e) = self.gl_surface.swap_buffers(&self.gl_context) {
+            log_error!("settings", "Failed to present frame: {e}");
+        }
+    }
+}
+
+impl Drop for EmbeddedWindow {
+    fn drop(&mut self) {
+        if let Some(size) = self.app.window_size {
+            save_window_size(size);
+        }
+        self.egui_glow.destroy();
+    }
+}
@@ -1,11 +1,26 @@
-use crate::figma::FigmaState;
-use crate::settings::Settings;
-use crate::settings_window;
-use muda::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use crate::autostart;
+use crate::figma::FigmaTab;
+use crate::i18n::{Strings, fill};
+use crate::instance::{Command, Reply, Shared};
+use crate::log_error;
+use crate::log_info;
+use crate::log_warn;
+use crate::notifications::{self, Notification};
+use crate::pause::{self, PauseDuration, PauseState};
+use crate::policy;
+use crate::presence;
+use crate::schedule::{self, ScheduleOutcome};
+use crate::settings::{self, AppName, Settings};
+use crate::settings_window::{self, EmbeddedWindow};
+use chrono::Timelike;
+use egui_glow::egui_winit::accesskit_winit;
+use image::imageops::FilterType;
+use muda::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
 use std::{
     sync::{
-        Arc, RwLock,
+        Arc, OnceLock,
         atomic::{AtomicBool, Ordering},
+        mpsc::Sender,
     },
     time::{Duration, Instant},
 };
@@ -13,56 +28,278 @@ use tray_icon::{TrayIcon, TrayIconBuilder};
 use winit::{
     application::ApplicationHandler,
     event::WindowEvent,
-    event_loop::{ActiveEventLoop, ControlFlow},
+    event_loop::{ActiveEventLoop, ControlFlow, EventLoopProxy},
     window::WindowId,
 };
 
-fn make_icon() -> tray_icon::Icon {
-    let image = image::load_from_memory(include_bytes!("../assets/favicon.png"))
-        .expect("failed to load tray icon")
-        .into_rgba8();
+/// Events sent to the tray's event loop from other threads.
+#[derive(Debug)]
+pub enum TrayEvent {
+    AccessKit(accesskit_winit::Event),
+    Menu(MenuEvent),
+    /// Something the menu, tooltip or icon shows may have changed.
+    StateChanged,
+    /// A request from a later launch or a control command, with where to
+    /// send the reply.
+    Control(Command, Sender<Reply>),
+}
+
+/// Wakes the tray to pick up changed state.
+pub fn notify(proxy: &EventLoopProxy<TrayEvent>) {
+    let _ = proxy.send_event(TrayEvent::StateChanged);
+}
+
+impl From<accesskit_winit::Event> for TrayEvent {
+    fn from(event: accesskit_winit::Event) -> Self {
+        Self::AccessKit(event)
+    }
+}
+
+/// What the dot in the corner of the tray icon shows.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum IconStatus {
+    Broadcasting,
+    Idle,
+    Disconnected,
+    Privacy,
+}
+
+impl IconStatus {
+    fn color(self) -> [u8; 3] {
+        match self {
+            Self::Broadcasting => [59, 165, 93],
+            Self::Idle => [250, 168, 26],
+            Self::Disconnected => [237, 66, 69],
+            Self::Privacy => [128, 132, 142],
+        }
+    }
+}
+
+const TRAY_ICON_SIZE: u32 = 64;
+
+/// The app icon with a status dot in the bottom right corner, cut out from
+/// the logo so it stays visible on light and dark panels.
+fn make_icon(status: IconStatus) -> tray_icon::Icon {
+    // The bundled icon is far larger than any tray, so decode and scale it
+    // down once rather than on every status change.
+    static BASE: OnceLock<image::RgbaImage> = OnceLock::new();
+    let mut image = BASE
+        .get_or_init(|| {
+            let image = image::load_from_memory(include_bytes!("../assets/favicon.png"))
+                .expect("failed to load tray icon");
+            image
+                .resize(TRAY_ICON_SIZE, TRAY_ICON_SIZE, FilterType::Lanczos3)
+                .into_rgba8()
+        })
+        .clone();
     let (width, height) = image.dimensions();
+    let size = width.min(height) as f32;
+    let radius = size * 0.22;
+    let gap = (size * 0.06).max(1.0);
+    let center_x = width as f32 - radius - gap;
+    let center_y = height as f32 - radius - gap;
+    let color = status.color();
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        let distance = (x as f32 + 0.5 - center_x).hypot(y as f32 + 0.5 - center_y);
+        // Coverage of the dot and of the gap around it, softened over a pixel.
+        let dot = (radius + 0.5 - distance).clamp(0.0, 1.0);
+        let cut = (radius + gap + 0.5 - distance).clamp(0.0, 1.0);
+        if cut == 0.0 {
+            continue;
+        }
+        let base = pixel[3] as f32 / 255.0 * (1.0 - cut);
+        let alpha = dot + base * (1.0 - dot);
+        for (channel, dot_channel) in pixel.0.iter_mut().zip(color) {
+            *channel = if alpha > 0.0 {
+                ((dot_channel as f32 * dot + *channel as f32 * base * (1.0 - dot)) / alpha).round()
+                    as u8
+            } else {
+                0
+            };
+        }
+        pixel[3] = (alpha * 255.0).round() as u8;
+    }
     tray_icon::Icon::from_rgba(image.into_raw(), width, height).expect("failed to create tray icon")
 }
 
-const TRAY_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+/// How many files the Recent Files submenu lists.
+const RECENT_FILES: usize = 10;
+
+/// A file in the Recent Files submenu, with its actions.
+struct RecentFile {
+    tab: FigmaTab,
+    file: String,
+    title: String,
+    link: Option<String>,
+    menu: Submenu,
+    hide_name: CheckMenuItem,
+    set_alias: MenuItem,
+    exclude: CheckMenuItem,
+    copy_link: MenuItem,
+}
+
+impl RecentFile {
+    fn new(tab: &FigmaTab, strings: &Strings) -> Option<Self> {
+        let file = tab.file_id()?;
+        let title = tab.title.clone().unwrap_or_else(|| file.clone());
+        let product = tab.editor_type.clone().unwrap_or_default().product_name();
+        let link = tab.link();
+        let menu = Submenu::new(format!("{title} ({product})"), true);
+        let entry = Self {
+            tab: tab.clone(),
+            file,
+            title,
+            menu,
+            hide_name: CheckMenuItem::new(strings.file_hide_name, true, false, None),
+            set_alias: MenuItem::new(strings.file_set_alias, true, None),
+            exclude: CheckMenuItem::new(strings.file_exclude, true, false, None),
+            copy_link: MenuItem::new(strings.file_copy_link, link.is_some(), None),
+            link,
+        };
+        entry.menu.append(&entry.hide_name).unwrap();
+        entry.menu.append(&entry.set_alias).unwrap();
+        entry.menu.append(&entry.exclude).unwrap();
+        entry.menu.append(&PredefinedMenuItem::separator()).unwrap();
+        entry.menu.append(&entry.copy_link).unwrap();
+        Some(entry)
+    }
+
+    /// Whether the entry still stands for `tab`. The view time is left out,
+    /// as it changes whenever the tab is looked at.
+    fn shows(&self, tab: &FigmaTab) -> bool {
+        self.tab.path == tab.path
+            && self.tab.title == tab.title
+            && self.tab.editor_type == tab.editor_type
+    }
+}
+
+enum FileAction {
+    HideName,
+    SetAlias,
+    Exclude,
+    CopyLink,
+}
+
+/// The status texts last set, so unchanged ones aren't set again.
+#[derive(PartialEq)]
+struct StatusTexts {
+    figma: &'static str,
+    discord: &'static str,
+    privacy: String,
+    presence: String,
+    paused: bool,
+}
 
 pub struct TrayApp {
-    pub running: Arc<AtomicBool>,
-    figma_connected: Arc<AtomicBool>,
-    discord_connected: Arc<AtomicBool>,
-    settings: Arc<RwLock<Settings>>,
+    shared: Shared,
     settings_open: Arc<AtomicBool>,
+    settings_window: Option<EmbeddedWindow>,
     quit_item: MenuItem,
     settings_item: MenuItem,
     figma_status: MenuItem,
     discord_status: MenuItem,
+    privacy_status: MenuItem,
+    presence_status: MenuItem,
+    pause_menu: Submenu,
+    pause_items: Vec<(MenuItem, PauseDuration)>,
+    resume_item: MenuItem,
+    hide_filename_item: CheckMenuItem,
+    disable_idle_item: CheckMenuItem,
+    launch_at_login_item: CheckMenuItem,
+    /// The start on login state last applied to the OS.
+    launch_at_login: bool,
+    app_name_menu: Submenu,
+    /// The custom entry remembers the last custom name, so it can be picked
+    /// again after switching to one of the Figma names.
+    app_name_items: Vec<(CheckMenuItem, AppName)>,
+    recent_menu: Submenu,
+    recent_placeholder: MenuItem,
+    recent_files: Vec<RecentFile>,
+    clipboard: Option<arboard::Clipboard>,
+    profiles_menu: Submenu,
+    profile_items: Vec<(CheckMenuItem, String)>,
+    settings_notice: Option<MenuItem>,
+    menu: Option<Menu>,
     tray: Option<TrayIcon>,
-    figma_state: Arc<RwLock<FigmaState>>,
-    last_tray_update: Instant,
+    icon_status: IconStatus,
+    status_texts: Option<StatusTexts>,
+    tooltip: String,
+    /// When time alone changes what the tray shows next: a pause running
+    /// out, a schedule rule starting or ending, or going idle.
+    refresh_at: Option<Instant>,
+    /// The catalog the menu texts were last set from.
+    strings: &'static Strings,
+    proxy: EventLoopProxy<TrayEvent>,
 }
 
 impl TrayApp {
-    pub fn new(
-        running: Arc<AtomicBool>,
-        figma_state: Arc<RwLock<FigmaState>>,
-        figma_connected: Arc<AtomicBool>,
-        discord_connected: Arc<AtomicBool>,
-        settings: Arc<RwLock<Settings>>,
-    ) -> Self {
+    pub fn new(shared: Shared, proxy: EventLoopProxy<TrayEvent>) -> Self {
+        let (strings, custom_name, launch_at_login) = {
+            let s = shared.settings.read().unwrap();
+            let custom_name = match &s.app_name {
+                AppName::Custom(name) => name.clone(),
+                _ => String::new(),
+            };
+            (s.strings(), custom_name, s.launch_at_login)
+        };
+        let app_name_items = vec![
+            (
+                CheckMenuItem::new("Figma", true, false, None),
+                AppName::Figma,
+            ),
+            (
+                CheckMenuItem::new("Figma Desktop", true, false, None),
+                AppName::FigmaDesktop,
+            ),
+            (
+                CheckMenuItem::new(strings.app_name_custom, false, false, None),
+                AppName::Custom(custom_name),
+            ),
+        ];
+        let pause_items = PauseDuration::ALL
+            .iter()
+            .map(|&duration| (MenuItem::new(duration.label(strings), true, None), duration))
+            .collect();
+        // Menu clicks arrive as user events, so they wake the loop and none
+        // are left queued.
+        let menu_proxy = proxy.clone();
+        MenuEvent::set_event_handler(Some(move |event| {
+            let _ = menu_proxy.send_event(TrayEvent::Menu(event));
+        }));
         Self {
-            running,
-            figma_connected,
-            discord_connected,
-            settings,
+            shared,
             settings_open: Arc::new(AtomicBool::new(false)),
-            quit_item: MenuItem::new("Quit", true, None),
-            settings_item: MenuItem::new("Settings...", true, None),
-            figma_status: MenuItem::new("Figma: Connecting...", false, None),
-            discord_status: MenuItem::new("Discord: Connecting...", false, None),
+            settings_window: None,
+            quit_item: MenuItem::new(strings.menu_quit, true, None),
+            settings_item: MenuItem::new(strings.menu_settings, true, None),
+            figma_status: MenuItem::new(strings.figma_connecting, false, None),
+            discord_status: MenuItem::new(strings.discord_connecting, false, None),
+            privacy_status: MenuItem::new(strings.privacy_off, false, None),
+            presence_status: MenuItem::new(strings.presence_active, false, None),
+            pause_menu: Submenu::new(strings.menu_pause, true),
+            pause_items,
+            resume_item: MenuItem::new(strings.menu_resume, false, None),
+            hide_filename_item: CheckMenuItem::new(strings.hide_file_names, true, false, None),
+            disable_idle_item: CheckMenuItem::new(strings.disable_idle, true, false, None),
+            launch_at_login_item: CheckMenuItem::new(strings.start_on_login, true, false, None),
+            launch_at_login,
+            app_name_menu: Submenu::new(strings.app_name, true),
+            app_name_items,
+            recent_menu: Submenu::new(strings.menu_recent_files, true),
+            recent_placeholder: MenuItem::new(strings.tooltip_no_file, false, None),
+            recent_files: Vec::new(),
+            clipboard: None,
+            profiles_menu: Submenu::new(strings.menu_profiles, true),
+            profile_items: Vec::new(),
+            settings_notice: None,
+            menu: None,
             tray: None,
-            figma_state,
-            last_tray_update: Instant::now(),
+            icon_status: IconStatus::Disconnected,
+            status_texts: None,
+            tooltip: String::new(),
+            refresh_at: None,
+            strings,
+            proxy,
         }
     }
 
@@ -78,76 +315,641 @@ impl TrayApp {
         menu.append(&PredefinedMenuItem::separator()).unwrap();
         menu.append(&self.discord_status).unwrap();
         menu.append(&self.figma_status).unwrap();
+        menu.append(&self.privacy_status).unwrap();
+        menu.append(&self.presence_status).unwrap();
+        menu.append(&PredefinedMenuItem::separator()).unwrap();
+        for (item, _) in &self.pause_items {
+            self.pause_menu.append(item).unwrap();
+        }
+        menu.append(&self.pause_menu).unwrap();
+        menu.append(&self.resume_item).unwrap();
         menu.append(&PredefinedMenuItem::separator()).unwrap();
+        menu.append(&self.hide_filename_item).unwrap();
+        menu.append(&self.disable_idle_item).unwrap();
+        menu.append(&self.launch_at_login_item).unwrap();
+        for (item, _) in &self.app_name_items {
+            self.app_name_menu.append(item).unwrap();
+        }
+        menu.append(&self.app_name_menu).unwrap();
+        self.refresh_toggles();
+        self.recent_menu.append(&self.recent_placeholder).unwrap();
+        self.refresh_recent_files();
+        menu.append(&self.recent_menu).unwrap();
+        menu.append(&PredefinedMenuItem::separator()).unwrap();
+        self.refresh_profiles();
+        menu.append(&self.profiles_menu).unwrap();
         menu.append(&self.settings_item).unwrap();
         menu.append(&self.quit_item).unwrap();
+        self.menu = Some(menu.clone());
         self.tray = Some(
             TrayIconBuilder::new()
                 .with_menu(Box::new(menu))
                 .with_tooltip("Figma Rich Presence")
-                .with_icon(make_icon())
+                .with_icon(make_icon(self.icon_status))
                 .build()
                 .unwrap(),
         );
     }
 
-    fn update_status_items(&self) {
-        let figma_text = if self.figma_connected.load(Ordering::Relaxed) {
-            "Figma: Connected"
+    /// Rebuilds the Profiles submenu when profiles were added, renamed or
+    /// removed, and keeps the check mark on the active profile.
+    fn refresh_profiles(&mut self) {
+        let (names, active) = {
+            let s = self.shared.settings.read().unwrap();
+            (s.profile_names(), s.active_profile.clone())
+        };
+        if self
+            .profile_items
+            .iter()
+            .map(|(_, name)| name)
+            .ne(names.iter())
+        {
+            for (item, _) in self.profile_items.drain(..) {
+                let _ = self.profiles_menu.remove(&item);
+            }
+            for name in names {
+                let item = CheckMenuItem::new(&name, true, false, None);
+                self.profiles_menu.append(&item).unwrap();
+                self.profile_items.push((item, name));
+            }
+        }
+        for (item, name) in &self.profile_items {
+            item.set_checked(*name == active);
+        }
+    }
+
+    /// Relabels the fixed menu items after the language was changed. Status
+    /// items are rewritten on every update anyway.
+    fn refresh_language(&mut self) {
+        let strings = self.shared.settings.read().unwrap().strings();
+        if std::ptr::eq(strings, self.strings) {
+            return;
+        }
+        self.strings = strings;
+        self.pause_menu.set_text(strings.menu_pause);
+        for (item, duration) in &self.pause_items {
+            item.set_text(duration.label(strings));
+        }
+        self.resume_item.set_text(strings.menu_resume);
+        self.hide_filename_item.set_text(strings.hide_file_names);
+        self.disable_idle_item.set_text(strings.disable_idle);
+        self.launch_at_login_item.set_text(strings.start_on_login);
+        self.app_name_menu.set_text(strings.app_name);
+        self.recent_menu.set_text(strings.menu_recent_files);
+        self.recent_placeholder.set_text(strings.tooltip_no_file);
+        for entry in &self.recent_files {
+            entry.hide_name.set_text(strings.file_hide_name);
+            entry.set_alias.set_text(strings.file_set_alias);
+            entry.exclude.set_text(strings.file_exclude);
+            entry.copy_link.set_text(strings.file_copy_link);
+        }
+        self.profiles_menu.set_text(strings.menu_profiles);
+        self.settings_item.set_text(strings.menu_settings);
+        self.quit_item.set_text(strings.menu_quit);
+    }
+
+    /// Keeps the quick toggles in step with the settings, which may also
+    /// change through the settings window or the command line.
+    fn refresh_toggles(&mut self) {
+        let s = self.shared.settings.read().unwrap();
+        self.hide_filename_item.set_checked(s.hide_filename);
+        self.hide_filename_item
+            .set_enabled(!policy::is_locked("hide_filename"));
+        self.disable_idle_item.set_checked(s.disable_idle);
+        self.disable_idle_item
+            .set_enabled(!policy::is_locked("disable_idle"));
+        self.launch_at_login_item.set_checked(s.launch_at_login);
+        self.launch_at_login_item
+            .set_enabled(!policy::is_locked("launch_at_login"));
+        if s.launch_at_login != self.launch_at_login {
+            self.launch_at_login = s.launch_at_login;
+            autostart::apply(s.launch_at_login);
+        }
+        let app_name_locked = policy::is_locked("app_name");
+        for (item, choice) in &mut self.app_name_items {
+            if let (AppName::Custom(current), AppName::Custom(remembered)) = (&s.app_name, &*choice)
+                && current != remembered
+            {
+                *choice = s.app_name.clone();
+            }
+            if let AppName::Custom(name) = &*choice {
+                let known = !name.trim().is_empty();
+                item.set_text(if known {
+                    name.as_str()
+                } else {
+                    s.strings().app_name_custom
+                });
+                item.set_enabled(known && !app_name_locked);
+            } else {
+                item.set_enabled(!app_name_locked);
+            }
+            item.set_checked(*choice == s.app_name);
+        }
+    }
+
+    /// Rebuilds the Recent Files submenu when other files are open in Figma,
+    /// and shows which rules apply to each.
+    fn refresh_recent_files(&mut self) {
+        let tabs: Vec<FigmaTab> = self
+            .shared
+            .figma_state
+            .read()
+            .unwrap()
+            .open_tabs
+            .iter()
+            .filter(|tab| tab.file_id().is_some())
+            .take(RECENT_FILES)
+            .cloned()
+            .collect();
+        let unchanged = self.recent_files.len() == tabs.len()
+            && self
+                .recent_files
+                .iter()
+                .zip(&tabs)
+                .all(|(entry, tab)| entry.shows(tab));
+        if !unchanged {
+            for entry in self.recent_files.drain(..) {
+                let _ = self.recent_menu.remove(&entry.menu);
+            }
+            let strings = self.strings;
+            self.recent_files = tabs
+                .iter()
+                .filter_map(|tab| RecentFile::new(tab, strings))
+                .collect();
+            for entry in &self.recent_files {
+                self.recent_menu.append(&entry.menu).unwrap();
+            }
+            if self.recent_files.is_empty() {
+                let _ = self.recent_menu.append(&self.recent_placeholder);
+            } else {
+                let _ = self.recent_menu.remove(&self.recent_placeholder);
+            }
+        }
+
+        let s = self.shared.settings.read().unwrap();
+        let locked = policy::is_locked("privacy.files");
+        for entry in &self.recent_files {
+            let rule = s.file_rule(&entry.file);
+            entry
+                .hide_name
+                .set_checked(rule.is_some_and(|r| r.hide_name));
+            entry.exclude.set_checked(rule.is_some_and(|r| r.exclude));
+            for item in [&entry.hide_name, &entry.exclude] {
+                item.set_enabled(!locked);
+            }
+            entry.set_alias.set_enabled(!locked);
+        }
+    }
+
+    fn recent_file_action(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        index: usize,
+        action: FileAction,
+    ) {
+        let entry = &self.recent_files[index];
+        let (file, title) = (entry.file.clone(), entry.title.clone());
+        match action {
+            FileAction::HideName => {
+                self.change_settings(|s| {
+                    s.update_file_rule(&file, &title, |r| r.hide_name = !r.hide_name)
+                });
+            }
+            FileAction::Exclude => {
+                self.change_settings(|s| {
+                    s.update_file_rule(&file, &title, |r| r.exclude = !r.exclude)
+                });
+            }
+            FileAction::SetAlias => {
+                // The menu can't take text, so the alias is entered (and
+                // saved) in the settings window.
+                self.open_settings(event_loop, Some((file, title)));
+            }
+            FileAction::CopyLink => {
+                let Some(link) = entry.link.clone() else {
+                    return;
+                };
+                if self.clipboard.is_none() {
+                    match arboard::Clipboard::new() {
+                        Ok(clipboard) => self.clipboard = Some(clipboard),
+                        Err(e) => {
+                            log_warn!("tray", "Clipboard unavailable: {e}");
+                            return;
+                        }
+                    }
+                }
+                // The clipboard is kept open, since on X11 its contents
+                // are only served while it is.
+                if let Some(clipboard) = &mut self.clipboard
+                    && let Err(e) = clipboard.set_text(link)
+                {
+                    log_warn!("tray", "Failed to copy link: {e}");
+                }
+            }
+        }
+    }
+
+    /// Applies a change made from the menu and saves it right away, on top
+    /// of the file as it is now so edits the settings process saved since
+    /// the last reload aren't undone.
+    fn change_settings(&mut self, change: impl FnOnce(&mut Settings)) {
+        {
+            let mut s = self.shared.settings.write().unwrap();
+            match settings::update(|current| {
+                change(current);
+                Ok(())
+            }) {
+                Ok(updated) => *s = updated,
+                Err(e) => {
+                    log_error!("settings", "Failed to save: {e:#}");
+                }
+            }
+        }
+        self.refresh();
+    }
+
+    /// Surfaces settings problems (see [`settings::take_notice`]) as a menu
+    /// item right below the title.
+    fn show_settings_notice(&mut self) {
+        let Some(menu) = &self.menu else { return };
+        let Some(notice) = settings::take_notice() else {
+            return;
+        };
+        let notice = notice.text(self.strings);
+        notifications::show(
+            &self.shared.settings.read().unwrap(),
+            Notification::SettingsProblem,
+            &notice,
+        );
+        let text = format!("⚠ {notice}");
+        match &self.settings_notice {
+            Some(item) => item.set_text(text),
+            None => {
+                let item = MenuItem::new(text, false, None);
+                let _ = menu.insert(&item, 2);
+                self.settings_notice = Some(item);
+            }
+        }
+    }
+
+    fn switch_profile(&mut self, name: &str) {
+        if self.shared.settings.read().unwrap().active_profile == name {
+            self.refresh();
+            return;
+        }
+        self.change_settings(|s| {
+            if s.switch_profile(name) {
+                log_info!("settings", "Switched to profile \"{name}\"");
+            }
+        });
+    }
+
+    /// Shows the settings window on this event loop, or in a separate
+    /// process when an OpenGL window can't be created here. `alias` names a
+    /// file (key and title) whose alias to start editing.
+    fn open_settings(&mut self, event_loop: &ActiveEventLoop, alias: Option<(String, String)>) {
+        if let Some(window) = &mut self.settings_window {
+            if let Some((file, title)) = &alias {
+                window.edit_alias(file, title);
+            }
+            window.focus();
+            return;
+        }
+        if self.settings_open.load(Ordering::Relaxed) {
+            return;
+        }
+        match EmbeddedWindow::open(
+            event_loop,
+            Arc::clone(&self.shared.settings),
+            Arc::clone(&self.shared.figma_state),
+            Arc::clone(&self.shared.privacy_active),
+            self.proxy.clone(),
+        ) {
+            Ok(mut window) => {
+                if let Some((file, title)) = &alias {
+                    window.edit_alias(file, title);
+                }
+                self.settings_window = Some(window);
+            }
+            Err(e) => {
+                log_warn!("tray", "Opening settings in a separate process: {e:#}");
+                settings_window::open(
+                    Arc::clone(&self.shared.settings),
+                    Arc::clone(&self.settings_open),
+                    self.proxy.clone(),
+                    alias,
+                );
+            }
+        }
+    }
+
+    /// Brings everything the tray shows up to date. Only what changed is
+    /// touched, so this is cheap to call on every notification.
+    fn refresh(&mut self) {
+        self.refresh_language();
+        self.check_pause_expiry();
+        self.refresh_profiles();
+        self.refresh_toggles();
+        self.refresh_recent_files();
+        self.show_settings_notice();
+        self.update_status_items();
+        self.update_tooltip();
+        self.update_icon();
+    }
+
+    /// The next moment the tray has to refresh without being told to.
+    fn next_refresh(&self) -> Option<Instant> {
+        let now = Instant::now();
+        let mut next = None::<Instant>;
+        let mut consider = |at: Instant| next = Some(next.map_or(at, |n| n.min(at)));
+
+        if let PauseState::Until(until) = *self.shared.pause_state.read().unwrap() {
+            let left = (until - chrono::Local::now()).to_std().unwrap_or_default();
+            consider(now + left);
+        }
+        let settings = self.shared.settings.read().unwrap();
+        if settings.schedule.iter().any(|rule| rule.enabled) {
+            // Rules start and end on the minute.
+            let second = chrono::Local::now().second() as u64;
+            consider(now + Duration::from_secs(60 - second));
+        }
+        let idle_threshold = settings.advanced.intervals().idle_threshold;
+        if !settings.disable_idle
+            && let Some(focused_at) = self.shared.figma_state.read().unwrap().last_focused_at
+            && focused_at.elapsed() < idle_threshold
+        {
+            consider(focused_at + idle_threshold);
+        }
+        next
+    }
+
+    fn handle_menu_event(&mut self, event_loop: &ActiveEventLoop, event: MenuEvent) {
+        if event.id() == self.quit_item.id() {
+            self.quit(event_loop);
+        } else if event.id() == self.settings_item.id() {
+            self.open_settings(event_loop, None);
+        } else if event.id() == self.resume_item.id() {
+            pause::persist(PauseState::Active);
+            self.set_paused(PauseState::Active);
+        } else if let Some(&(_, duration)) = self
+            .pause_items
+            .iter()
+            .find(|(item, _)| event.id() == item.id())
+        {
+            self.set_paused(PauseState::start(duration));
+        } else if event.id() == self.hide_filename_item.id() {
+            self.change_settings(|s| s.hide_filename = !s.hide_filename);
+        } else if event.id() == self.disable_idle_item.id() {
+            self.change_settings(|s| s.disable_idle = !s.disable_idle);
+        } else if event.id() == self.launch_at_login_item.id() {
+            self.change_settings(|s| s.launch_at_login = !s.launch_at_login);
+        } else if let Some((_, choice)) = self
+            .app_name_items
+            .iter()
+            .find(|(item, _)| event.id() == item.id())
+        {
+            let choice = choice.clone();
+            self.change_settings(|s| s.app_name = choice);
+        } else if let Some((index, action)) =
+            self.recent_files
+                .iter()
+                .enumerate()
+                .find_map(|(index, entry)| {
+                    let action = if event.id() == entry.hide_name.id() {
+                        FileAction::HideName
+                    } else if event.id() == entry.set_alias.id() {
+                        FileAction::SetAlias
+                    } else if event.id() == entry.exclude.id() {
+                        FileAction::Exclude
+                    } else if event.id() == entry.copy_link.id() {
+                        FileAction::CopyLink
+                    } else {
+                        return None;
+                    };
+                    Some((index, action))
+                })
+        {
+            self.recent_file_action(event_loop, index, action);
+        } else if let Some((_, name)) = self
+            .profile_items
+            .iter()
+            .find(|(item, _)| event.id() == item.id())
+        {
+            let name = name.clone();
+            self.switch_profile(&name);
+        }
+    }
+
+    fn update_status_items(&mut self) {
+        let strings = self.strings;
+        let figma_text = if self.shared.figma_connected.load(Ordering::Relaxed) {
+            strings.figma_connected
         } else {
-            "Figma: Disconnected"
+            strings.figma_disconnected
         };
-        let discord_text = if self.discord_connected.load(Ordering::Relaxed) {
-            "Discord: Connected"
+        let discord_text = if self.shared.discord_connected.load(Ordering::Relaxed) {
+            strings.discord_connected
         } else {
-            "Discord: Disconnected"
+            strings.discord_disconnected
+        };
+        let privacy_text = match &*self.shared.privacy_active.read().unwrap() {
+            Some(name) => fill(strings.privacy_on, &[name]),
+            None => strings.privacy_off.to_string(),
+        };
+        let pause_text = self.shared.pause_state.read().unwrap().describe(strings);
+        let paused = pause_text.is_some();
+        let schedule = schedule::evaluate(
+            &self.shared.settings.read().unwrap().schedule,
+            chrono::Local::now().naive_local(),
+        );
+        let presence_text = match (pause_text, schedule) {
+            (Some(text), _) => fill(strings.presence_paused, &[&text]),
+            (None, ScheduleOutcome::Disabled) => strings.presence_schedule_off.to_string(),
+            (None, ScheduleOutcome::Privacy) => strings.presence_schedule_private.to_string(),
+            (None, ScheduleOutcome::Normal) => strings.presence_active.to_string(),
         };
-        self.figma_status.set_text(figma_text);
-        self.discord_status.set_text(discord_text);
+        let texts = StatusTexts {
+            figma: figma_text,
+            discord: discord_text,
+            privacy: privacy_text,
+            presence: presence_text,
+            paused,
+        };
+        let last = self.status_texts.as_ref();
+        if last.is_none_or(|last| last.figma != texts.figma) {
+            self.figma_status.set_text(texts.figma);
+        }
+        if last.is_none_or(|last| last.discord != texts.discord) {
+            self.discord_status.set_text(texts.discord);
+        }
+        if last.is_none_or(|last| last.privacy != texts.privacy) {
+            self.privacy_status.set_text(&texts.privacy);
+        }
+        if last.is_none_or(|last| last.presence != texts.presence) {
+            self.presence_status.set_text(&texts.presence);
+        }
+        if last.is_none_or(|last| last.paused != texts.paused) {
+            self.resume_item.set_enabled(texts.paused);
+        }
+        self.status_texts = Some(texts);
+    }
+
+    fn handle_command(&mut self, event_loop: &ActiveEventLoop, command: Command) -> Reply {
+        if let Some(reply) = self.shared.handle(command) {
+            self.refresh();
+            return reply;
+        }
+        match command {
+            Command::OpenSettings => self.open_settings(event_loop, None),
+            Command::ShowStatus => {
+                self.refresh();
+                notifications::show_now(self.strings.already_running, &self.tooltip);
+            }
+            Command::Quit => self.quit(event_loop),
+            _ => {}
+        }
+        Reply::Done
+    }
+
+    fn quit(&self, event_loop: &ActiveEventLoop) {
+        self.shared.running.store(false, Ordering::Relaxed);
+        event_loop.exit();
     }
 
-    fn update_tooltip(&self) {
+    fn set_paused(&mut self, state: PauseState) {
+        self.shared.set_paused(state);
+        self.update_status_items();
+        self.update_tooltip();
+        self.update_icon();
+    }
+
+    fn check_pause_expiry(&mut self) {
+        let expired = self.shared.pause_state.read().unwrap().has_expired();
+        if expired {
+            pause::persist(PauseState::Active);
+            self.set_paused(PauseState::Active);
+        }
+    }
+
+    /// Mirrors what the presence loop is doing: broadcasting, holding back
+    /// (idle, paused, no file or scheduled off), or blocked.
+    fn current_icon_status(&self) -> IconStatus {
+        if !self.shared.figma_connected.load(Ordering::Relaxed)
+            || !self.shared.discord_connected.load(Ordering::Relaxed)
+        {
+            return IconStatus::Disconnected;
+        }
+        let settings = self.shared.settings.read().unwrap();
+        let schedule = schedule::evaluate(&settings.schedule, chrono::Local::now().naive_local());
+        if self.shared.pause_state.read().unwrap().is_paused()
+            || schedule == ScheduleOutcome::Disabled
+        {
+            return IconStatus::Idle;
+        }
+        if self.shared.privacy_active.read().unwrap().is_some()
+            || schedule == ScheduleOutcome::Privacy
+        {
+            return IconStatus::Privacy;
+        }
+        let figma = self.shared.figma_state.read().unwrap();
+        let idle =
+            figma.is_idle(settings.advanced.intervals().idle_threshold) && !settings.disable_idle;
+        let shown = figma
+            .active_tab
+            .as_ref()
+            .is_some_and(|tab| !presence::is_excluded(&settings, tab));
+        if !shown || idle {
+            IconStatus::Idle
+        } else {
+            IconStatus::Broadcasting
+        }
+    }
+
+    fn update_icon(&mut self) {
         let Some(tray) = &self.tray else { return };
-        let state = self.figma_state.read().unwrap();
-        let tooltip = match &state.active_tab {
-            None => "Figma Rich Presence — No file open".to_string(),
-            Some(tab) => {
-                let title = tab.title.as_deref().unwrap_or("Unknown");
-                let status = state.status();
-                format!("Figma Rich Presence — {status}: {title}")
+        let status = self.current_icon_status();
+        if status == self.icon_status {
+            return;
+        }
+        self.icon_status = status;
+        if let Err(e) = tray.set_icon(Some(make_icon(status))) {
+            log_warn!("tray", "Failed to update icon: {e}");
+        }
+    }
+
+    fn update_tooltip(&mut self) {
+        let Some(tray) = &self.tray else { return };
+        let profile = self.shared.settings.read().unwrap().active_profile.clone();
+        let state = self.shared.figma_state.read().unwrap();
+        let privacy = self.shared.privacy_active.read().unwrap();
+        let strings = self.strings;
+        let paused = self.shared.pause_state.read().unwrap().describe(strings);
+        let detail = match (&state.active_tab, &*privacy, paused) {
+            (_, _, Some(text)) => text,
+            (_, Some(name), None) => fill(strings.tooltip_privacy, &[name]),
+            (None, None, None) => strings.tooltip_no_file.to_string(),
+            (Some(tab), None, None) => {
+                let title = tab.title.as_deref().unwrap_or(strings.unknown_file);
+                let status = strings.state(state.state_key());
+                format!("{status}: {title}")
             }
         };
-        let _ = tray.set_tooltip(Some(&tooltip));
+        let tooltip = format!("Figma Rich Presence [{profile}] — {detail}");
+        if tooltip != self.tooltip {
+            let _ = tray.set_tooltip(Some(&tooltip));
+            self.tooltip = tooltip;
+        }
     }
 }
 
-impl ApplicationHandler for TrayApp {
+impl ApplicationHandler<TrayEvent> for TrayApp {
     fn resumed(&mut self, _event_loop: &ActiveEventLoop) {
         if self.tray.is_none() {
             self.init_tray();
+            self.refresh();
         }
     }
 
-    fn window_event(&mut self, _: &ActiveEventLoop, _: WindowId, _: WindowEvent) {}
-
-    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
-        event_loop.set_control_flow(ControlFlow::WaitUntil(
-            Instant::now() + Duration::from_millis(200),
-        ));
+    fn window_event(&mut self, _: &ActiveEventLoop, id: WindowId, event: WindowEvent) {
+        if let Some(window) = &mut self.settings_window
+            && window.id() == id
+            && window.on_window_event(&event)
+        {
+            self.settings_window = None;
+        }
+    }
 
-        if let Ok(event) = MenuEvent::receiver().try_recv() {
-            if event.id() == self.quit_item.id() {
-                self.running.store(false, Ordering::Relaxed);
-                event_loop.exit();
-            } else if event.id() == self.settings_item.id() {
-                settings_window::open(Arc::clone(&self.settings), Arc::clone(&self.settings_open));
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, event: TrayEvent) {
+        match event {
+            TrayEvent::AccessKit(event) => {
+                if let Some(window) = &mut self.settings_window
+                    && window.id() == event.window_id
+                {
+                    window.on_accesskit_event(&event.window_event);
+                }
+            }
+            TrayEvent::Menu(event) => self.handle_menu_event(event_loop, event),
+            TrayEvent::StateChanged => self.refresh(),
+            TrayEvent::Control(command, reply) => {
+                let _ = reply.send(self.handle_command(event_loop, command));
             }
         }
+    }
 
-        if self.last_tray_update.elapsed() >= TRAY_UPDATE_INTERVAL {
-            self.update_status_items();
-            self.update_tooltip();
-            self.last_tray_update = Instant::now();
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        if self.refresh_at.is_some_and(|at| at <= Instant::now()) {
+            self.refresh();
         }
+        self.refresh_at = self.next_refresh();
+
+        let repaint_at = self
+            .settings_window
+            .as_ref()
+            .and_then(EmbeddedWindow::poll_repaint);
+        let wake_at = [self.refresh_at, repaint_at].into_iter().flatten().min();
+        event_loop.set_control_flow(match wake_at {
+            Some(at) => ControlFlow::WaitUntil(at),
+            None => ControlFlow::Wait,
+        });
     }
 }